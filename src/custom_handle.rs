@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use bevy::{pbr::NotShadowCaster, prelude::*, render::view::RenderLayers};
+use bevy_mod_picking::selection::NoDeselect;
+
+use crate::{
+    gizmo_render_layer, GizmoLayerOffset, GizmoOverlay, GizmoPickCamera, PickableGizmo,
+    TransformGizmo, TransformGizmoInteraction, TransformGizmoSettings,
+};
+
+/// A handle provided by the app, moving the selection its own way when dragged. Spawn it as a
+/// child of a [`TransformGizmo`](crate::TransformGizmo) entity, along with its mesh and material,
+/// and it is picked and grabbed like the built-in handles. It keeps its own material, so it is not
+/// highlighted when hovered, nor faded while another handle is dragged.
+#[derive(Component, Clone)]
+pub struct CustomGizmoHandle(pub Arc<dyn GizmoHandleDrag>);
+
+impl CustomGizmoHandle {
+    pub fn new(drag: impl GizmoHandleDrag) -> Self {
+        Self(Arc::new(drag))
+    }
+}
+
+/// What a custom handle is given to move the selection with, every frame of a drag.
+#[derive(Debug)]
+pub struct CustomHandleDrag<'a> {
+    /// The current picking ray.
+    pub ray: Ray,
+    /// The picking ray when the drag started.
+    pub start_ray: Ray,
+    /// Position of the gizmo when the drag started.
+    pub gizmo_origin: Vec3,
+    /// Global transforms of the selected entities when the drag started.
+    pub initial_transforms: &'a [Transform],
+}
+
+/// Moves the selection while a [`CustomGizmoHandle`] is dragged. Implemented for closures.
+pub trait GizmoHandleDrag: Send + Sync + 'static {
+    /// Returns the new global transform of each selected entity, in the order of
+    /// `drag.initial_transforms`.
+    fn drag(&self, drag: &CustomHandleDrag) -> Vec<Transform>;
+}
+
+impl<F> GizmoHandleDrag for F
+where
+    F: Fn(&CustomHandleDrag) -> Vec<Transform> + Send + Sync + 'static,
+{
+    fn drag(&self, drag: &CustomHandleDrag) -> Vec<Transform> {
+        self(drag)
+    }
+}
+
+/// Makes new custom handles pickable like the built-in ones, on the render layer of their gizmo.
+pub fn register_custom_handles(
+    settings: Res<TransformGizmoSettings>,
+    handles: Query<(Entity, Option<&Parent>), Added<CustomGizmoHandle>>,
+    gizmos: Query<&GizmoPickCamera, With<TransformGizmo>>,
+    cameras: Query<(Option<&GizmoOverlay>, Option<&GizmoLayerOffset>)>,
+    mut commands: Commands,
+) {
+    for (entity, parent) in &handles {
+        let render_layer = parent
+            .and_then(|parent| gizmos.get(parent.get()).ok())
+            .and_then(|pick_camera| cameras.get(pick_camera.0).ok())
+            .map_or(settings.render_layer, |(overlay, offset)| {
+                gizmo_render_layer(&settings, overlay, offset)
+            });
+        commands.entity(entity).insert((
+            TransformGizmoInteraction::Custom,
+            PickableGizmo::default(),
+            NoDeselect,
+            NotShadowCaster,
+            RenderLayers::layer(render_layer),
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_mod_picking::prelude::Pickable;
+use bevy_mod_raycast::prelude::RaycastMethod;
+
+use crate::{
+    GizmoControlEvent, GizmoPickCamera, GizmoPickSource, TransformGizmo, TransformGizmoInteraction,
+    TransformGizmoSettings,
+};
+
+/// Controls the gizmo with a gamepad. The left stick moves a virtual cursor over the view, and the
+/// shoulder buttons cycle through the handles, moving the cursor onto them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GizmoGamepadControls {
+    /// Speed of the virtual cursor at full stick deflection, in logical pixels per second.
+    pub cursor_speed: f32,
+    /// Hold to drag the handle under the virtual cursor.
+    pub grab: GamepadButtonType,
+    pub next_handle: GamepadButtonType,
+    pub previous_handle: GamepadButtonType,
+}
+
+impl Default for GizmoGamepadControls {
+    fn default() -> Self {
+        Self {
+            cursor_speed: 600.0,
+            grab: GamepadButtonType::South,
+            next_handle: GamepadButtonType::RightTrigger,
+            previous_handle: GamepadButtonType::LeftTrigger,
+        }
+    }
+}
+
+/// The virtual cursor moved with the gamepad, for the app to draw. Moving it from the app works as
+/// well.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct GizmoVirtualCursor {
+    /// The picking camera the cursor is over.
+    pub camera: Option<Entity>,
+    /// Position of the cursor, in logical pixels of the camera's render target.
+    pub position: Vec2,
+}
+
+/// Moves the virtual cursor with the left stick of any gamepad, and casts the gizmo's picking ray
+/// from it.
+pub fn move_virtual_cursor(
+    settings: Res<TransformGizmoSettings>,
+    time: Res<Time>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut cursor: ResMut<GizmoVirtualCursor>,
+    mut cameras: Query<(Entity, &mut GizmoPickSource, &Camera)>,
+) {
+    let Some(controls) = settings.gamepad else {
+        return;
+    };
+    // Start in the middle of the view of the first active picking camera.
+    if !cursor.camera.is_some_and(|camera| cameras.contains(camera)) {
+        let Some((camera, viewport)) = cameras
+            .iter()
+            .filter(|(_, _, camera)| camera.is_active)
+            .find_map(|(entity, _, camera)| Some((entity, camera.logical_viewport_rect()?)))
+        else {
+            return;
+        };
+        *cursor = GizmoVirtualCursor {
+            camera: Some(camera),
+            position: viewport.center(),
+        };
+    }
+    let Some(Ok((_, mut pick_source, camera))) =
+        cursor.camera.map(|entity| cameras.get_mut(entity))
+    else {
+        return;
+    };
+    let Some(viewport) = camera.logical_viewport_rect() else {
+        return;
+    };
+
+    let stick: Vec2 = gamepads
+        .iter()
+        .map(|gamepad| {
+            let axis = |axis_type| {
+                axes.get(GamepadAxis::new(gamepad, axis_type))
+                    .unwrap_or(0.0)
+            };
+            Vec2::new(
+                axis(GamepadAxisType::LeftStickX),
+                axis(GamepadAxisType::LeftStickY),
+            )
+        })
+        .sum();
+    if stick != Vec2::ZERO {
+        // Screen space is y-down.
+        let delta = Vec2::new(stick.x, -stick.y) * controls.cursor_speed * time.delta_seconds();
+        cursor.position = (cursor.position + delta).clamp(viewport.min, viewport.max);
+    }
+    if cursor.is_changed() {
+        pick_source.cast_method = RaycastMethod::Screenspace(cursor.position);
+    }
+}
+
+/// Hovers the handle under the virtual cursor, cycles through the handles with the shoulder
+/// buttons, and grabs the hovered handle while the grab button is held.
+#[allow(clippy::too_many_arguments)]
+pub fn control_with_gamepad(
+    settings: Res<TransformGizmoSettings>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    mut cursor: ResMut<GizmoVirtualCursor>,
+    pick_sources: Query<(&GizmoPickSource, &Camera, &GlobalTransform)>,
+    gizmos: Query<(Entity, &TransformGizmo, &GizmoPickCamera, &Children)>,
+    handles: Query<(
+        &TransformGizmoInteraction,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&Aabb>,
+        Option<&Pickable>,
+    )>,
+    // The handle picked with the shoulder buttons, and where the cursor was moved to show it.
+    mut focused: Local<Option<(Entity, Vec2)>>,
+    mut events: EventWriter<GizmoControlEvent>,
+) {
+    let Some(controls) = settings.gamepad else {
+        return;
+    };
+    let just_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    let just_released = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| buttons.just_released(GamepadButton::new(gamepad, button_type)))
+    };
+    let Some((gizmo_entity, gizmo, pick_camera, children)) = gizmos
+        .iter()
+        .find(|(_, _, pick_camera, _)| Some(pick_camera.0) == cursor.camera)
+    else {
+        return;
+    };
+    let Ok((pick_source, camera, camera_transform)) = pick_sources.get(pick_camera.0) else {
+        return;
+    };
+
+    if gizmo.current_interaction().is_some() {
+        if just_released(controls.grab) {
+            events.send(GizmoControlEvent::Release {
+                gizmo: gizmo_entity,
+            });
+        }
+        return;
+    }
+
+    // Handles that can be grabbed, once per interaction as arrows are made of several meshes.
+    let is_grabbable = |entity: &Entity| {
+        handles
+            .get(*entity)
+            .is_ok_and(|(_, _, visibility, _, pickable)| {
+                visibility.get() && pickable.map_or(true, |pickable| pickable.should_emit_events)
+            })
+    };
+    let mut grabbable: Vec<(Entity, TransformGizmoInteraction, Vec3)> = Vec::new();
+    for entity in children.iter().filter(|entity| is_grabbable(entity)) {
+        let Ok((interaction, transform, _, aabb, _)) = handles.get(*entity) else {
+            continue;
+        };
+        if grabbable.iter().any(|(_, i, _)| i == interaction) {
+            continue;
+        }
+        let center = aabb.map_or(Vec3::ZERO, |aabb| aabb.center.into());
+        grabbable.push((*entity, *interaction, transform.transform_point(center)));
+    }
+
+    // Moving the cursor away from a focused handle goes back to hovering what is under it.
+    if focused.is_some_and(|(_, position)| position != cursor.position) {
+        *focused = None;
+    }
+    let count = grabbable.len();
+    let current = focused.and_then(|(entity, _)| grabbable.iter().position(|(e, ..)| *e == entity));
+    let next = match (
+        just_pressed(controls.next_handle),
+        just_pressed(controls.previous_handle),
+        current,
+    ) {
+        _ if count == 0 => None,
+        (true, false, Some(index)) => Some((index + 1) % count),
+        (true, false, None) => Some(0),
+        (false, true, Some(index)) => Some((index + count - 1) % count),
+        (false, true, None) => Some(count - 1),
+        _ => None,
+    };
+    if let Some(next) = next {
+        let (entity, _, center) = grabbable[next];
+        let viewport_min = camera.logical_viewport_rect().map_or(Vec2::ZERO, |r| r.min);
+        if let Some(position) = camera.world_to_viewport(camera_transform, center) {
+            cursor.position = position + viewport_min;
+        }
+        *focused = Some((entity, cursor.position));
+    }
+
+    let hovered = focused.map(|(entity, _)| entity).or_else(|| {
+        pick_source
+            .intersections()
+            .iter()
+            .map(|(entity, _)| *entity)
+            .find(|entity| children.contains(entity) && is_grabbable(entity))
+    });
+    if gizmo.hovered_handle != hovered {
+        events.send(GizmoControlEvent::Hover {
+            gizmo: gizmo_entity,
+            handle: hovered,
+        });
+    }
+    if let (Some(handle), true) = (hovered, just_pressed(controls.grab)) {
+        events.send(GizmoControlEvent::Grab { handle });
+    }
+}
@@ -5,7 +5,8 @@ use bevy::{
     render::{
         mesh::MeshVertexBufferLayout,
         render_resource::{
-            AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+            AsBindGroup, CompareFunction, RenderPipelineDescriptor, ShaderRef,
+            SpecializedMeshPipelineError,
         },
     },
 };
@@ -13,14 +14,33 @@ use bevy::{
 pub const GIZMO_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(13953800272683943019);
 
 #[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+#[bind_group_data(GizmoMaterialKey)]
 pub struct GizmoMaterial {
     #[uniform(0)]
     pub color: Color,
+    /// Only draw the parts of the mesh that are hidden behind other geometry.
+    pub occluded: bool,
 }
 
 impl From<Color> for GizmoMaterial {
     fn from(color: Color) -> Self {
-        GizmoMaterial { color }
+        GizmoMaterial {
+            color,
+            occluded: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GizmoMaterialKey {
+    occluded: bool,
+}
+
+impl From<&GizmoMaterial> for GizmoMaterialKey {
+    fn from(material: &GizmoMaterial) -> Self {
+        GizmoMaterialKey {
+            occluded: material.occluded,
+        }
     }
 }
 
@@ -45,9 +65,16 @@ impl Material for GizmoMaterial {
         _pipeline: &MaterialPipeline<Self>,
         descriptor: &mut RenderPipelineDescriptor,
         _layout: &MeshVertexBufferLayout,
-        _key: MaterialPipelineKey<Self>,
+        key: MaterialPipelineKey<Self>,
     ) -> Result<(), SpecializedMeshPipelineError> {
         descriptor.primitive.cull_mode = None;
+        if key.bind_group_data.occluded {
+            if let Some(depth_stencil) = descriptor.depth_stencil.as_mut() {
+                // Depth is reversed, so occluded fragments are the ones with a lower depth.
+                depth_stencil.depth_compare = CompareFunction::Less;
+                depth_stencil.depth_write_enabled = false;
+            }
+        }
         Ok(())
     }
 }
@@ -0,0 +1,164 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+
+use crate::{
+    gizmo_material::GizmoMaterial, mesh::RotationSweep, GizmoDragDelta, TransformGizmo,
+    TransformGizmoInteraction, TransformGizmoSettings,
+};
+
+/// Length of the guide lines drawn along the active axis, long enough to appear infinite.
+const GUIDE_LENGTH: f32 = 10_000.0;
+/// Number of grid lines drawn on each side of the gizmo origin for plane drags.
+const GRID_HALF_LINES: i32 = 10;
+/// Spacing of the plane grid, in the gizmo's local units.
+const GRID_SPACING: f32 = 0.5;
+/// Number of segments used per radian of the rotation sweep.
+const SWEEP_SEGMENTS_PER_RADIAN: f32 = 16.0;
+
+/// Draws guides showing the direction of the active drag: a line through the gizmo origin along
+/// the active axis, or a grid on the active plane.
+pub fn draw_guides(
+    settings: Res<TransformGizmoSettings>,
+    gizmo_query: Query<(&TransformGizmo, &GlobalTransform)>,
+    mut gizmos: Gizmos,
+) {
+    if !settings.show_guides {
+        return;
+    }
+    for (gizmo, transform) in &gizmo_query {
+        let Some(interaction) = gizmo.current_interaction() else {
+            continue;
+        };
+        // Anchor the guides where the drag started, so the selection visibly moves along them.
+        let origin = gizmo
+            .initial_transform
+            .map_or(transform.translation(), |t| t.translation());
+        match interaction {
+            TransformGizmoInteraction::TranslateAxis { original, axis }
+            | TransformGizmoInteraction::RotateAxis { original, axis } => {
+                let color = settings.palette.axis_color(original);
+                let axis = axis.normalize();
+                gizmos.line(
+                    origin - axis * GUIDE_LENGTH,
+                    origin + axis * GUIDE_LENGTH,
+                    color,
+                );
+            }
+            // The view translation handle has no fixed plane to draw a grid on.
+            TransformGizmoInteraction::TranslatePlane { original, normal }
+                if original != Vec3::ZERO =>
+            {
+                let color = settings
+                    .palette
+                    .axis_color(original)
+                    .with_a(settings.palette.plane_alpha);
+                let normal = normal.normalize();
+                let u = normal.any_orthonormal_vector();
+                let v = normal.cross(u);
+                let (scale, ..) = transform.to_scale_rotation_translation();
+                let spacing = GRID_SPACING * scale.max_element();
+                let extent = spacing * GRID_HALF_LINES as f32;
+                for i in -GRID_HALF_LINES..=GRID_HALF_LINES {
+                    let offset = spacing * i as f32;
+                    gizmos.line(
+                        origin + u * offset - v * extent,
+                        origin + u * offset + v * extent,
+                        color,
+                    );
+                    gizmos.line(
+                        origin + v * offset - u * extent,
+                        origin + v * offset + u * extent,
+                        color,
+                    );
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Fills the sector between the start of a rotation drag and the current cursor direction, and
+/// draws its two spokes.
+pub fn draw_rotation_sweep(
+    settings: Res<TransformGizmoSettings>,
+    gizmo_query: Query<(&TransformGizmo, &GlobalTransform, &Children)>,
+    mut sweeps: Query<
+        (&Handle<Mesh>, &Handle<GizmoMaterial>, &mut Visibility),
+        With<RotationSweep>,
+    >,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<GizmoMaterial>>,
+    mut gizmos: Gizmos,
+) {
+    for (gizmo, transform, children) in &gizmo_query {
+        let mut sweep_iter = sweeps.iter_many_mut(children);
+        let Some((mesh, material, mut visibility)) = sweep_iter.fetch_next() else {
+            continue;
+        };
+        let (
+            Some(TransformGizmoInteraction::RotateAxis { original, axis }),
+            Some(GizmoDragDelta::Rotation(angle)),
+            Some(start),
+        ) = (
+            gizmo.current_interaction,
+            gizmo.drag_delta,
+            gizmo.drag_start,
+        )
+        else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+
+        let color = settings.palette.axis_color(original);
+        if let Some(material) = materials.get_mut(material) {
+            material.color = color.with_a(settings.palette.plane_alpha);
+        }
+
+        // The sweep is a child of the gizmo, so its mesh is built in the gizmo's local space.
+        let to_local = transform.affine().inverse();
+        let local_start = to_local.transform_vector3(start).normalize();
+        let local_axis = to_local.transform_vector3(axis).normalize();
+        if let Some(mesh) = meshes.get_mut(mesh) {
+            *mesh = sector_mesh(local_start, local_axis, angle, settings.shapes.ring_radius);
+        }
+
+        let origin = transform.translation();
+        let end = Quat::from_axis_angle(axis.normalize(), angle) * start;
+        let radius = transform
+            .affine()
+            .transform_vector3(local_start * settings.shapes.ring_radius)
+            .length();
+        gizmos.line(origin, origin + start * radius, color);
+        gizmos.line(origin, origin + end * radius, color);
+    }
+}
+
+/// Builds a triangle fan covering the sector of radius `radius` swept by rotating
+/// `start` by `angle` radians around `axis`.
+fn sector_mesh(start: Vec3, axis: Vec3, angle: f32, radius: f32) -> Mesh {
+    let segments = ((angle.abs() * SWEEP_SEGMENTS_PER_RADIAN).ceil() as usize).max(1);
+    let mut positions: Vec<[f32; 3]> = Vec::with_capacity(segments + 2);
+    let mut indices: Vec<u32> = Vec::with_capacity(segments * 3);
+
+    positions.push([0.0, 0.0, 0.0]);
+    for segment in 0..=segments {
+        let rotation = Quat::from_axis_angle(axis, angle * segment as f32 / segments as f32);
+        positions.push((rotation * start * radius).into());
+    }
+    for segment in 0..segments as u32 {
+        indices.extend([0, segment + 1, segment + 2]);
+    }
+
+    let normals = vec![<[f32; 3]>::from(axis); positions.len()];
+    let uvs = vec![[0.0, 0.0]; positions.len()];
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh
+}
@@ -0,0 +1,379 @@
+use bevy::{prelude::*, render::primitives::Aabb, window::PrimaryWindow};
+use bevy_mod_picking::{
+    backend::{HitData, PointerHits},
+    pointer::{PointerId, PointerLocation},
+    prelude::Pickable,
+};
+
+use crate::{
+    picking::{pointer_position, target_to_viewport, DEFAULT_TARGET_RECT_TOLERANCE},
+    GizmoPickCamera, GizmoPickSource, GizmoTargetRect, InternalGizmoCamera, TransformGizmo,
+    TransformGizmoInteraction, TransformGizmoSettings,
+};
+
+/// Number of segments a ring is split into when measuring the distance to it.
+const RING_SEGMENTS: usize = 64;
+
+/// The shape a handle is picked by, worked out from its interaction and the bounds of its mesh.
+enum HandleShape {
+    /// The view handle at the center of the gizmo.
+    Center,
+    Plane,
+    Axis,
+    Ring,
+}
+
+impl HandleShape {
+    /// Custom handles have no known shape, and are only picked by their meshes.
+    fn of(interaction: &TransformGizmoInteraction) -> Option<Self> {
+        match *interaction {
+            TransformGizmoInteraction::TranslatePlane { original, .. }
+                if original == Vec3::ZERO =>
+            {
+                Some(HandleShape::Center)
+            }
+            TransformGizmoInteraction::TranslatePlane { .. } => Some(HandleShape::Plane),
+            TransformGizmoInteraction::TranslateAxis { .. }
+            | TransformGizmoInteraction::ScaleAxis { .. } => Some(HandleShape::Axis),
+            TransformGizmoInteraction::RotateAxis { .. } => Some(HandleShape::Ring),
+            TransformGizmoInteraction::Custom => None,
+        }
+    }
+
+    /// Overlapping handles are picked in this order, lowest first.
+    fn priority(&self) -> f32 {
+        match self {
+            HandleShape::Center => 0.0,
+            HandleShape::Plane => 1.0,
+            HandleShape::Axis => 2.0,
+            HandleShape::Ring => 3.0,
+        }
+    }
+
+    /// Depth of a hit `distance` pixels away. Closer handles of the same kind come first, without
+    /// reaching the next kind.
+    fn depth(&self, distance: f32) -> f32 {
+        self.priority() + distance / (distance + 1.0)
+    }
+}
+
+/// A `bevy_mod_picking` backend that picks gizmo handles by the distance, in logical pixels, from
+/// each pointer to the projected axis segments, plane quads, rings and center of the gizmo, rather
+/// than by raycasting their thin meshes. Its hits are placed just above the overlay camera, and
+/// sorted by handle kind so overlapping handles are picked deterministically.
+///
+/// Without a [`TransformGizmoSettings::handle_pick_tolerance`], only pointers in the window showing
+/// a camera with a [`GizmoTargetRect`] are picked, as `bevy_mod_picking` can't pick those.
+#[allow(clippy::too_many_arguments)]
+pub fn pick_handles(
+    settings: Res<TransformGizmoSettings>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    pointers: Query<(&PointerId, &PointerLocation)>,
+    cameras: Query<(&Camera, &GlobalTransform, Option<&GizmoTargetRect>), With<GizmoPickSource>>,
+    overlay_cameras: Query<(Entity, &Camera, &GizmoPickCamera), With<InternalGizmoCamera>>,
+    gizmos: Query<(&GizmoPickCamera, &Children), With<TransformGizmo>>,
+    handles: Query<(
+        Entity,
+        &TransformGizmoInteraction,
+        &GlobalTransform,
+        &InheritedVisibility,
+        &Aabb,
+        Option<&Pickable>,
+    )>,
+    mut output: EventWriter<PointerHits>,
+) {
+    let primary_window = primary_window.get_single().ok();
+    for (pointer, location) in &pointers {
+        let Some(location) = location.location() else {
+            continue;
+        };
+        for (pick_camera, children) in &gizmos {
+            let Ok((camera, camera_transform, target_rect)) = cameras.get(pick_camera.0) else {
+                continue;
+            };
+            let Some((overlay, overlay_camera, _)) = overlay_cameras
+                .iter()
+                .find(|(_, _, overlay_pick_camera)| *overlay_pick_camera == pick_camera)
+            else {
+                continue;
+            };
+            if !camera.is_active {
+                continue;
+            }
+            // Pointers on the camera's own render target are already picked by its meshes.
+            let on_target =
+                Some(&location.target) == camera.target.normalize(primary_window).as_ref();
+            let tolerance = match (settings.handle_pick_tolerance, on_target) {
+                (Some(tolerance), _) => tolerance,
+                (None, false) => DEFAULT_TARGET_RECT_TOLERANCE,
+                (None, true) => continue,
+            };
+            let Some(cursor) = pointer_position(camera, target_rect, primary_window, location)
+                .and_then(|position| target_to_viewport(camera, position))
+            else {
+                continue;
+            };
+            if camera.logical_viewport_size().map_or(true, |size| {
+                !Rect::from_corners(Vec2::ZERO, size).contains(cursor)
+            }) {
+                continue;
+            }
+            // Fingers are less precise than a mouse.
+            let tolerance = match pointer {
+                PointerId::Touch(_) => tolerance * settings.touch_hit_scale,
+                _ => tolerance,
+            };
+            let project = |point: Vec3| camera.world_to_viewport(camera_transform, point);
+
+            let mut picks = Vec::new();
+            for (entity, interaction, transform, visibility, aabb, pickable) in
+                handles.iter_many(children)
+            {
+                if !visibility.get()
+                    || pickable.is_some_and(|pickable| !pickable.should_emit_events)
+                {
+                    continue;
+                }
+                let Some(shape) = HandleShape::of(interaction) else {
+                    continue;
+                };
+                let distance = match shape {
+                    HandleShape::Center => {
+                        distance_to_sphere(camera_transform, transform, aabb, cursor, &project)
+                    }
+                    HandleShape::Plane => distance_to_quad(transform, aabb, cursor, &project),
+                    HandleShape::Axis => distance_to_segment(transform, aabb, cursor, &project),
+                    HandleShape::Ring => distance_to_ring(transform, aabb, cursor, &project),
+                };
+                let Some(distance) = distance.filter(|distance| *distance <= tolerance) else {
+                    continue;
+                };
+                let depth = shape.depth(distance);
+                picks.push((entity, HitData::new(overlay, depth, None, None)));
+            }
+            if !picks.is_empty() {
+                output.send(PointerHits::new(
+                    *pointer,
+                    picks,
+                    overlay_camera.order as f32 + 0.5,
+                ));
+            }
+        }
+    }
+}
+
+/// Distance from `cursor` to the projected sphere bounding the handle, zero inside of it.
+fn distance_to_sphere(
+    camera_transform: &GlobalTransform,
+    transform: &GlobalTransform,
+    aabb: &Aabb,
+    cursor: Vec2,
+    project: &impl Fn(Vec3) -> Option<Vec2>,
+) -> Option<f32> {
+    let center = transform.transform_point(aabb.center.into());
+    let radius = transform
+        .transform_point(Vec3::from(aabb.center) + Vec3::X * aabb.half_extents.max_element())
+        .distance(center);
+    let screen_center = project(center)?;
+    let screen_radius =
+        project(center + camera_transform.right() * radius)?.distance(screen_center);
+    Some((cursor.distance(screen_center) - screen_radius).max(0.0))
+}
+
+/// Distance from `cursor` to the projected quad spanned by the two largest extents of the handle,
+/// zero inside of it.
+fn distance_to_quad(
+    transform: &GlobalTransform,
+    aabb: &Aabb,
+    cursor: Vec2,
+    project: &impl Fn(Vec3) -> Option<Vec2>,
+) -> Option<f32> {
+    let center = Vec3::from(aabb.center);
+    let half_extents = Vec3::from(aabb.half_extents);
+    let thin = thinnest_axis(half_extents);
+    let u = Vec3::AXES[(thin + 1) % 3] * half_extents[(thin + 1) % 3];
+    let v = Vec3::AXES[(thin + 2) % 3] * half_extents[(thin + 2) % 3];
+    let corners = [
+        project(transform.transform_point(center - u - v))?,
+        project(transform.transform_point(center + u - v))?,
+        project(transform.transform_point(center + u + v))?,
+        project(transform.transform_point(center - u + v))?,
+    ];
+    let edges = [0, 1, 2, 3].map(|i| (corners[i], corners[(i + 1) % 4]));
+    // Inside when the cursor is on the same side of every edge, whichever way the quad faces.
+    let sides = edges.map(|(a, b)| (b - a).perp_dot(cursor - a));
+    if sides.iter().all(|side| *side >= 0.0) || sides.iter().all(|side| *side <= 0.0) {
+        return Some(0.0);
+    }
+    edges
+        .iter()
+        .map(|(a, b)| point_to_segment(cursor, *a, *b))
+        .reduce(f32::min)
+}
+
+/// Distance from `cursor` to the projected segment along the longest extent of the handle.
+fn distance_to_segment(
+    transform: &GlobalTransform,
+    aabb: &Aabb,
+    cursor: Vec2,
+    project: &impl Fn(Vec3) -> Option<Vec2>,
+) -> Option<f32> {
+    let center = Vec3::from(aabb.center);
+    let half_extents = Vec3::from(aabb.half_extents);
+    let long = (0..3)
+        .max_by(|a, b| half_extents[*a].total_cmp(&half_extents[*b]))
+        .unwrap_or(1);
+    let half = Vec3::AXES[long] * half_extents[long];
+    let start = project(transform.transform_point(center - half))?;
+    let end = project(transform.transform_point(center + half))?;
+    Some(point_to_segment(cursor, start, end))
+}
+
+/// Distance from `cursor` to the projected ring of a torus around the thinnest extent of the
+/// handle. Only the part of the ring within the bounds of the mesh is kept, so arcs work too.
+fn distance_to_ring(
+    transform: &GlobalTransform,
+    aabb: &Aabb,
+    cursor: Vec2,
+    project: &impl Fn(Vec3) -> Option<Vec2>,
+) -> Option<f32> {
+    let half_extents = Vec3::from(aabb.half_extents);
+    let (min, max) = (Vec3::from(aabb.min()), Vec3::from(aabb.max()));
+    let axis = thinnest_axis(half_extents);
+    let tube_radius = half_extents[axis];
+    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+    // The ring is centered on the handle's origin, and reaches the far side of its bounds.
+    let radius = [min[u], max[u], min[v], max[v]]
+        .into_iter()
+        .map(f32::abs)
+        .reduce(f32::max)?
+        - tube_radius;
+    // Parts of the ring are kept when the whole section of the tube fits in the bounds.
+    let slack = radius * 0.01;
+    let bounds = Rect::new(
+        min[u] - slack,
+        min[v] - slack,
+        max[u] + slack,
+        max[v] + slack,
+    );
+    let points: Vec<Option<Vec2>> = (0..=RING_SEGMENTS)
+        .map(|i| {
+            let angle = std::f32::consts::TAU * i as f32 / RING_SEGMENTS as f32;
+            let (sin, cos) = angle.sin_cos();
+            let direction = Vec2::new(cos, sin);
+            if !bounds.contains(direction * (radius + tube_radius))
+                || !bounds.contains(direction * (radius - tube_radius))
+            {
+                return None;
+            }
+            let local = direction * radius;
+            let mut point = Vec3::ZERO;
+            point[u] = local.x;
+            point[v] = local.y;
+            project(transform.transform_point(point))
+        })
+        .collect();
+    points
+        .windows(2)
+        .filter_map(|pair| Some(point_to_segment(cursor, pair[0]?, pair[1]?)))
+        .reduce(f32::min)
+}
+
+fn thinnest_axis(half_extents: Vec3) -> usize {
+    (0..3)
+        .min_by(|a, b| half_extents[*a].total_cmp(&half_extents[*b]))
+        .unwrap_or(1)
+}
+
+fn point_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let t = if segment.length_squared() > f32::EPSILON {
+        ((point - start).dot(segment) / segment.length_squared()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    point.distance(start + segment * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Looks down the Z axis, so world X and Y are screen X and Y.
+    fn top_view(point: Vec3) -> Option<Vec2> {
+        Some(point.truncate())
+    }
+
+    /// Looks up the Z axis, mirroring X and flipping the winding of everything on screen.
+    fn bottom_view(point: Vec3) -> Option<Vec2> {
+        Some(Vec2::new(-point.x, point.y))
+    }
+
+    fn assert_near(actual: Option<f32>, expected: f32) {
+        let actual = actual.unwrap();
+        assert!((actual - expected).abs() < 1e-3, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn point_to_segment_clamps_to_the_ends() {
+        let (start, end) = (Vec2::ZERO, Vec2::new(2.0, 0.0));
+        assert_near(Some(point_to_segment(Vec2::new(1.0, 1.0), start, end)), 1.0);
+        assert_near(
+            Some(point_to_segment(Vec2::new(-3.0, 0.0), start, end)),
+            3.0,
+        );
+        assert_near(Some(point_to_segment(Vec2::new(5.0, 4.0), start, end)), 5.0);
+        // A segment projected to a point.
+        assert_near(
+            Some(point_to_segment(Vec2::new(0.0, 2.0), start, start)),
+            2.0,
+        );
+    }
+
+    #[test]
+    fn distance_to_quad_for_both_windings() {
+        let transform = GlobalTransform::IDENTITY;
+        let aabb = Aabb::from_min_max(Vec3::new(-1.0, -1.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+        for project in [top_view, bottom_view] {
+            let distance = |cursor| distance_to_quad(&transform, &aabb, cursor, &project);
+            assert_near(distance(Vec2::ZERO), 0.0);
+            assert_near(distance(Vec2::new(0.5, -0.9)), 0.0);
+            assert_near(distance(Vec2::new(3.0, 0.0)), 2.0);
+            assert_near(distance(Vec2::new(0.0, -1.5)), 0.5);
+        }
+    }
+
+    #[test]
+    fn distance_to_quarter_arc_and_full_ring() {
+        let transform = GlobalTransform::IDENTITY;
+        // A ring of radius 1 around Z, made of a tube of radius 0.1.
+        let ring = Aabb::from_min_max(Vec3::new(-1.1, -1.1, -0.1), Vec3::new(1.1, 1.1, 0.1));
+        // Only the quarter from +X to +Y, whose ends are cut square.
+        let arc = Aabb::from_min_max(Vec3::new(0.0, 0.0, -0.1), Vec3::new(1.1, 1.1, 0.1));
+        let distance = |aabb, cursor| distance_to_ring(&transform, aabb, cursor, &top_view);
+
+        assert_near(distance(&ring, Vec2::new(1.5, 0.0)), 0.5);
+        assert_near(distance(&arc, Vec2::new(1.5, 0.0)), 0.5);
+        // Opposite the arc, the closest point of the arc is its end at +Y.
+        assert_near(distance(&ring, Vec2::new(-1.5, 0.0)), 0.5);
+        assert_near(distance(&arc, Vec2::new(-1.5, 0.0)), 1.5f32.hypot(1.0));
+    }
+
+    #[test]
+    fn overlapping_hits_sort_by_handle_kind() {
+        // A distant hit of a kind comes before the closest hit of the next kind.
+        let depths = [
+            HandleShape::Center.depth(100.0),
+            HandleShape::Plane.depth(0.0),
+            HandleShape::Plane.depth(100.0),
+            HandleShape::Axis.depth(0.0),
+            HandleShape::Axis.depth(100.0),
+            HandleShape::Ring.depth(0.0),
+        ];
+        assert!(
+            depths.windows(2).all(|pair| pair[0] < pair[1]),
+            "{depths:?}"
+        );
+        assert!(HandleShape::Axis.depth(1.0) < HandleShape::Axis.depth(2.0));
+    }
+}
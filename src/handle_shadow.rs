@@ -0,0 +1,53 @@
+use bevy::{pbr::NotShadowCaster, prelude::*, render::view::RenderLayers};
+use bevy_mod_picking::prelude::Pickable;
+
+use crate::gizmo_material::GizmoMaterial;
+
+/// An entity drawn with the mesh of a gizmo handle, following the handle around as its child, such
+/// as an occluded ghost or a touch hit area.
+pub trait HandleShadow: Component {
+    /// Transform of the shadow relative to its handle.
+    fn offset(&self) -> Transform {
+        Transform::IDENTITY
+    }
+}
+
+/// Spawns `shadow` as a child of `handle`, with the handle's mesh and render layers. It is not
+/// pickable, so the pointer goes through to the handle itself.
+pub fn spawn_handle_shadow(
+    commands: &mut Commands,
+    handle: Entity,
+    mesh: &Handle<Mesh>,
+    render_layers: Option<&RenderLayers>,
+    material: Handle<GizmoMaterial>,
+    shadow: impl Bundle,
+) -> Entity {
+    let entity = commands
+        .spawn((
+            MaterialMeshBundle {
+                mesh: mesh.clone(),
+                material,
+                ..Default::default()
+            },
+            Pickable::IGNORE,
+            NotShadowCaster,
+            render_layers.copied().unwrap_or_default(),
+            shadow,
+        ))
+        .id();
+    commands.entity(handle).add_child(entity);
+    entity
+}
+
+/// Moves the shadows along with their handles, whose transforms are set by the gizmo systems rather
+/// than by transform propagation.
+pub fn sync_handle_shadows<T: HandleShadow>(
+    mut shadows: Query<(&Parent, &T, &mut GlobalTransform)>,
+    handles: Query<&GlobalTransform, Without<T>>,
+) {
+    for (parent, shadow, mut global_transform) in &mut shadows {
+        if let Ok(handle_transform) = handles.get(parent.get()) {
+            *global_transform = handle_transform.mul_transform(shadow.offset());
+        }
+    }
+}
@@ -1,22 +1,45 @@
 #![allow(clippy::type_complexity)]
 
+use std::f32::consts::{PI, TAU};
+
 use bevy::asset::load_internal_asset;
-use bevy::{prelude::*, render::camera::Projection, transform::TransformSystem};
+use bevy::{
+    core_pipeline::core_3d::Camera3dDepthLoadOp,
+    prelude::*,
+    render::{camera::Projection, view::RenderLayers},
+    transform::TransformSystem,
+};
 use bevy_mod_picking::{
-    prelude::PickingInteraction,
+    picking_core::PickSet,
+    prelude::{Pickable, PickingInteraction, PointerId},
     selection::{NoDeselect, PickSelection},
 };
 use gizmo_material::GizmoMaterial;
-use mesh::{RotationGizmo, ViewTranslateGizmo};
+use mesh::{AxisLabel, HandleFade, RotationGizmo, ScreenSpaceDrag, ViewTranslateGizmo};
 use normalization::*;
 
+mod custom_handle;
+mod gamepad;
 mod gizmo_material;
+mod guides;
+mod handle_picking;
+mod handle_shadow;
 mod mesh;
 pub mod normalization;
+mod palette;
+#[cfg(feature = "delta_readout")]
+mod readout;
+mod shapes;
+mod touch;
+mod xray;
 
 pub mod picking;
 
-pub use picking::{GizmoPickSource, PickableGizmo};
+pub use custom_handle::{CustomGizmoHandle, CustomHandleDrag, GizmoHandleDrag};
+pub use gamepad::{GizmoGamepadControls, GizmoVirtualCursor};
+pub use palette::{AxisLabels, GizmoPalette};
+pub use picking::{GizmoPickSource, GizmoPickingMode, GizmoRay, GizmoTargetRect, PickableGizmo};
+pub use shapes::{AxisTip, GizmoHandleMeshes, GizmoShapes};
 
 #[derive(Resource, Clone, Debug)]
 pub struct GizmoSystemsEnabled(pub bool);
@@ -41,12 +64,70 @@ pub struct TransformGizmoEvent {
     pub interaction: TransformGizmoInteraction,
 }
 
+/// Drives a gizmo without a `bevy_mod_picking` pointer, following the ray of the
+/// [`GizmoPickSource`] of its camera. Use it with [`GizmoPickingMode::Manual`] to control the
+/// gizmo from any ray source, such as a gamepad moving a virtual cursor.
+#[derive(Debug, Clone, Copy, Event)]
+pub enum GizmoControlEvent {
+    /// Highlights `handle` as if it was hovered, or stops highlighting when `None`.
+    Hover {
+        gizmo: Entity,
+        handle: Option<Entity>,
+    },
+    /// Starts dragging `handle`, one of the entities with a [`TransformGizmoInteraction`].
+    Grab { handle: Entity },
+    /// Stops dragging.
+    Release { gizmo: Entity },
+}
+
 #[derive(Component, Default, Clone, Debug)]
 pub struct GizmoTransformable;
 
 #[derive(Component, Default, Clone, Debug)]
 pub struct InternalGizmoCamera;
 
+/// Render layer given to a picking camera without a [`GizmoOverlay`], as an offset from
+/// [`TransformGizmoSettings::render_layer`].
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+struct GizmoLayerOffset(u8);
+
+/// Render layer of the gizmo of a picking camera.
+fn gizmo_render_layer(
+    settings: &TransformGizmoSettings,
+    overlay: Option<&GizmoOverlay>,
+    offset: Option<&GizmoLayerOffset>,
+) -> u8 {
+    match (overlay, offset) {
+        (Some(overlay), _) => overlay.render_layer,
+        (None, Some(offset)) => settings.render_layer.saturating_add(offset.0),
+        (None, None) => settings.render_layer,
+    }
+}
+
+/// Links a gizmo and its overlay camera to the picking camera they are drawn for.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GizmoPickCamera(pub(crate) Entity);
+
+impl GizmoPickCamera {
+    /// The camera with the [`GizmoPickSource`].
+    pub fn camera(&self) -> Entity {
+        self.0
+    }
+}
+
+/// Overrides the render layer and camera order of the gizmo overlay. Insert it next to the
+/// [`GizmoPickSource`] of a camera.
+///
+/// Every picking camera gets its own gizmo, drawn on its own render layer so each camera only
+/// shows and picks its own gizmo. Cameras without a `GizmoOverlay` get the first free layer from
+/// [`TransformGizmoSettings::render_layer`] up.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GizmoOverlay {
+    pub render_layer: u8,
+    /// Added to the order of the picking camera to get the order of the overlay camera.
+    pub camera_order_offset: isize,
+}
+
 #[derive(Resource, Clone, Debug)]
 pub struct TransformGizmoSettings {
     pub enabled: bool,
@@ -55,6 +136,96 @@ pub struct TransformGizmoSettings {
     pub alignment_rotation: Quat,
     pub allow_rotation: bool,
     pub enable_shortcuts: bool,
+    /// Colors of the gizmo handles.
+    pub palette: GizmoPalette,
+    /// Glyphs drawn at the tip of each axis.
+    pub axis_labels: AxisLabels,
+    /// Alpha multiplier applied to every handle other than the one being dragged, for the
+    /// duration of the drag. Set to `0.0` to hide them.
+    pub inactive_handle_alpha: f32,
+    /// Draw a guide line along the active axis, or a grid on the active plane, while dragging.
+    pub show_guides: bool,
+    /// Show the current translation or rotation next to the cursor while dragging. Requires the
+    /// `delta_readout` feature.
+    pub show_delta_readout: bool,
+    /// Keys that scale down drags by `precision_factor` while held, for fine adjustments.
+    pub precision_modifiers: Vec<KeyCode>,
+    pub precision_factor: f32,
+    /// Translation axes within this angle, in radians, of the view direction are considered to
+    /// face the camera, and planes within this angle of being edge-on are considered edge-on.
+    /// Dragging such handles follows the cursor in screen space instead.
+    pub camera_facing_angle: f32,
+    /// Hide the translation axes facing the camera, as they can't be dragged precisely.
+    pub hide_camera_facing_handles: bool,
+    /// Maximum distance a single drag can move the selection.
+    pub max_drag_distance: Option<f32>,
+    /// Mirror the translation handles to the side of the gizmo facing the camera, so they are
+    /// never hidden behind the pivot.
+    pub flip_handles_toward_camera: bool,
+    /// Fade out translation handles seen end-on (axes), and translation planes and rotation rings
+    /// seen edge-on.
+    pub view_angle_fade: Option<ViewAngleFade>,
+    /// How the gizmo is drawn relative to the scene.
+    pub depth_mode: GizmoDepthMode,
+    /// First render layer the gizmos are drawn on by their overlay cameras. Each picking camera
+    /// gets the next free layer, unless it has a [`GizmoOverlay`].
+    pub render_layer: u8,
+    /// Added to the order of the picking camera to get the order of the overlay camera.
+    pub camera_order_offset: isize,
+    /// Where the picking ray used to drag the gizmo comes from.
+    pub picking_mode: GizmoPickingMode,
+    /// How much thicker the handles are to touch than they look, while touch input is in use.
+    /// Read when the gizmo is built.
+    pub touch_hit_scale: f32,
+    /// Scale the selection by pinching two fingers on it, and rotate it around the view axis by
+    /// twisting them.
+    pub allow_touch_gestures: bool,
+    /// Control the gizmo with a gamepad.
+    pub gamepad: Option<GizmoGamepadControls>,
+    /// Shapes and sizes of the handles. Read when the gizmo is built.
+    pub shapes: GizmoShapes,
+    /// Pick the handles by their distance to the pointer on screen, within this many logical
+    /// pixels, instead of only by raycasting their meshes. Where handles overlap, the center is
+    /// picked first, then planes, axes and rings. Pointers in the window showing a camera with a
+    /// [`GizmoTargetRect`] are always picked this way.
+    pub handle_pick_tolerance: Option<f32>,
+}
+
+/// How the gizmo is drawn relative to the rest of the scene.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GizmoDepthMode {
+    /// The gizmo is drawn on top of the scene.
+    #[default]
+    AlwaysOnTop,
+    /// The gizmo is depth tested against the scene, and the parts hidden behind it are drawn with
+    /// their alpha multiplied by `occluded_alpha`. Requires a 3D camera.
+    XRay { occluded_alpha: f32 },
+}
+
+/// Angles, in radians, between a handle and the view direction over which the handle fades out.
+/// For axes the angle is measured from the axis, for planes and rings from the plane they lie in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewAngleFade {
+    /// Handles start fading out below this angle.
+    pub start: f32,
+    /// Handles are fully faded out, and can't be picked, below this angle.
+    pub end: f32,
+}
+
+impl Default for ViewAngleFade {
+    fn default() -> Self {
+        Self {
+            start: 15f32.to_radians(),
+            end: 5f32.to_radians(),
+        }
+    }
+}
+
+impl ViewAngleFade {
+    /// Opacity of a handle at `angle` radians from the view direction.
+    fn opacity(&self, angle: f32) -> f32 {
+        ((angle - self.end) / (self.start - self.end)).clamp(0.0, 1.0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -63,6 +234,16 @@ pub struct TransformGizmoPlugin {
     // coordinate system.
     pub alignment_rotation: Quat,
     pub enable_shortcuts: bool,
+    // Colors of the gizmo handles.
+    pub palette: GizmoPalette,
+    // Glyphs drawn at the tip of each axis.
+    pub axis_labels: AxisLabels,
+    // Render layer the gizmo is drawn on by its overlay camera. Must not be used by the scene.
+    pub render_layer: u8,
+    // Added to the order of the picking camera to get the order of the overlay camera.
+    pub camera_order_offset: isize,
+    // Shapes and sizes of the handles.
+    pub shapes: GizmoShapes,
 }
 
 impl Default for TransformGizmoPlugin {
@@ -70,6 +251,11 @@ impl Default for TransformGizmoPlugin {
         Self {
             alignment_rotation: Quat::IDENTITY,
             enable_shortcuts: true,
+            palette: GizmoPalette::default(),
+            axis_labels: AxisLabels::None,
+            render_layer: 12,
+            camera_order_offset: 10,
+            shapes: GizmoShapes::default(),
         }
     }
 }
@@ -88,23 +274,98 @@ impl Plugin for TransformGizmoPlugin {
             alignment_rotation: self.alignment_rotation,
             allow_rotation: true,
             enable_shortcuts: self.enable_shortcuts,
+            palette: self.palette,
+            axis_labels: self.axis_labels,
+            inactive_handle_alpha: 0.15,
+            show_guides: true,
+            show_delta_readout: true,
+            precision_modifiers: vec![KeyCode::ShiftLeft, KeyCode::ShiftRight],
+            precision_factor: 0.1,
+            camera_facing_angle: 10f32.to_radians(),
+            hide_camera_facing_handles: false,
+            max_drag_distance: None,
+            flip_handles_toward_camera: false,
+            view_angle_fade: None,
+            depth_mode: GizmoDepthMode::AlwaysOnTop,
+            render_layer: self.render_layer,
+            camera_order_offset: self.camera_order_offset,
+            picking_mode: GizmoPickingMode::Raycast,
+            touch_hit_scale: 1.75,
+            allow_touch_gestures: true,
+            gamepad: None,
+            handle_pick_tolerance: None,
+            shapes: self.shapes.clone(),
         })
         .insert_resource(GizmoSystemsEnabled(true))
+        .init_resource::<touch::TouchInUse>()
+        .init_resource::<GizmoVirtualCursor>()
         .add_plugins((
             MaterialPlugin::<GizmoMaterial>::default(),
             picking::GizmoPickingPlugin,
             Ui3dNormalization,
         ))
-        .add_event::<TransformGizmoEvent>();
+        .add_event::<TransformGizmoEvent>()
+        .add_event::<GizmoControlEvent>();
+
+        #[cfg(feature = "delta_readout")]
+        app.add_plugins(readout::DeltaReadoutPlugin);
 
         // Input Set
         app.add_systems(
             PreUpdate,
-            update_gizmo_settings
+            (
+                spawn_gizmos,
+                custom_handle::register_custom_handles,
+                apply_deferred,
+                (update_gizmo_settings, xray::update_ghost_visibility),
+            )
+                .chain()
                 .in_set(TransformGizmoSystem::UpdateSettings)
                 .run_if(|settings: Res<TransformGizmoSettings>| settings.enabled),
         );
 
+        // Touch Input
+        app.add_systems(
+            PreUpdate,
+            (
+                touch::update_touch_hit_areas.before(PickSet::Backend),
+                touch::pinch_selection
+                    .after(PickSet::Focus)
+                    .run_if(|settings: Res<TransformGizmoSettings>| settings.allow_touch_gestures),
+            )
+                .run_if(|settings: Res<TransformGizmoSettings>| settings.enabled),
+        );
+
+        // Handle picking
+        app.add_systems(
+            PreUpdate,
+            handle_picking::pick_handles
+                .in_set(PickSet::Backend)
+                .run_if(
+                    |settings: Res<TransformGizmoSettings>,
+                     target_rects: Query<(), With<GizmoTargetRect>>| {
+                        settings.enabled
+                            && (settings.handle_pick_tolerance.is_some()
+                                || !target_rects.is_empty())
+                    },
+                ),
+        );
+
+        // Pointer-less control
+        app.add_systems(
+            PreUpdate,
+            (
+                gamepad::control_with_gamepad
+                    .run_if(|settings: Res<TransformGizmoSettings>| settings.gamepad.is_some()),
+                mesh::apply_control_events,
+                apply_deferred,
+                mesh::drag_controlled_gizmos,
+            )
+                .chain()
+                .after(TransformGizmoSystem::RaycastSet)
+                .run_if(|settings: Res<TransformGizmoSettings>| settings.enabled),
+        );
+
         // Main Set
         app.add_systems(
             PostUpdate,
@@ -116,16 +377,26 @@ impl Plugin for TransformGizmoPlugin {
                     .in_set(TransformGizmoSystem::Place)
                     .after(TransformSystem::TransformPropagate),
                 propagate_gizmo_elements,
-                adjust_view_translate_gizmo.in_set(TransformGizmoSystem::Drag),
+                adjust_view_translate_gizmo.in_set(TransformGizmoSystem::AdjustViewTranslateGizmo),
+                flip_handles_toward_camera.in_set(TransformGizmoSystem::AdjustViewTranslateGizmo),
+                orient_axis_labels.in_set(TransformGizmoSystem::Drag),
+                hide_camera_facing_handles.in_set(TransformGizmoSystem::Drag),
+                fade_handles_by_view_angle.in_set(TransformGizmoSystem::Drag),
                 gizmo_cam_copy_settings.in_set(TransformGizmoSystem::Drag),
+                update_render_layers,
+                mesh::update_handle_colors,
+                guides::draw_guides,
+                guides::draw_rotation_sweep,
+                xray::spawn_occluded_ghosts,
+                handle_shadow::sync_handle_shadows::<xray::OccludedGhost>,
+                xray::update_ghost_colors,
+                touch::spawn_touch_hit_areas,
+                handle_shadow::sync_handle_shadows::<touch::TouchHitArea>,
             )
                 .chain()
                 .in_set(TransformGizmoSystem::MainSet)
                 .run_if(|settings: Res<TransformGizmoSettings>| settings.enabled),
         );
-
-        app.add_systems(Startup, mesh::build_gizmo)
-            .add_systems(PostStartup, place_gizmo);
     }
 }
 
@@ -166,22 +437,151 @@ pub struct TransformGizmo {
     drag_start: Option<Vec3>,
     // Initial transform of the gizmo
     initial_transform: Option<GlobalTransform>,
+    // Handle under the pointer, highlighted along with the handles sharing its material.
+    hovered_handle: Option<Entity>,
+    // Handle being dragged, which stays highlighted until the drag ends.
+    active_handle: Option<Entity>,
+    // Total change applied to the selection since the drag started.
+    drag_delta: Option<GizmoDragDelta>,
+    // Tracks the precision modifier so that toggling it mid-drag doesn't make the selection jump.
+    precision: Option<PrecisionState>,
+    // Set when the drag can't be computed from the picking ray and follows the cursor instead.
+    screen_drag: Option<ScreenSpaceDrag>,
+    // The bevy_mod_picking pointer dragging the gizmo.
+    pointer: Option<PointerId>,
+    // Picking ray when a custom handle was grabbed.
+    start_ray: Option<Ray>,
 }
 
 impl TransformGizmo {
+    /// Starts dragging `handle`, with the pointer `pointer` or with [`GizmoControlEvent`]s.
+    fn begin_drag(
+        &mut self,
+        transform: GlobalTransform,
+        interaction: TransformGizmoInteraction,
+        handle: Entity,
+        pointer: Option<PointerId>,
+    ) {
+        self.initial_transform = Some(transform);
+        self.current_interaction = Some(interaction);
+        self.active_handle = Some(handle);
+        self.pointer = pointer;
+    }
+
+    /// Resets all the drag state, keeping only the hovered handle.
+    fn end_drag(&mut self) {
+        *self = TransformGizmo {
+            hovered_handle: self.hovered_handle,
+            ..default()
+        };
+    }
+
     /// Get the gizmo's drag direction.
     pub fn current_interaction(&self) -> Option<TransformGizmoInteraction> {
         self.current_interaction
     }
+
+    /// Get the total change applied to the selection by the current drag.
+    pub fn drag_delta(&self) -> Option<GizmoDragDelta> {
+        self.drag_delta
+    }
+
+    /// Converts the delta computed from the cursor position into the delta to apply to the
+    /// selection, scaling it by `factor` while `precise` is true. The delta is rebased whenever
+    /// `precise` changes, so the selection doesn't jump when the modifier is pressed or released.
+    fn apply_precision(
+        &mut self,
+        raw: GizmoDragDelta,
+        precise: bool,
+        factor: f32,
+    ) -> GizmoDragDelta {
+        let state = self.precision.get_or_insert(PrecisionState {
+            precise,
+            previous_raw: raw,
+            anchor_raw: raw.zero(),
+            anchor_applied: raw.zero(),
+        });
+        // Rotation angles wrap around at ±180°, unwrap them to keep the delta continuous.
+        let raw = match (raw, state.previous_raw) {
+            (GizmoDragDelta::Rotation(angle), GizmoDragDelta::Rotation(previous)) => {
+                let difference = (angle - previous + PI).rem_euclid(TAU) - PI;
+                GizmoDragDelta::Rotation(previous + difference)
+            }
+            _ => raw,
+        };
+        if state.precise != precise {
+            state.precise = precise;
+            state.anchor_raw = state.previous_raw;
+            state.anchor_applied = self.drag_delta.unwrap_or(raw.zero());
+        }
+        state.previous_raw = raw;
+        let scale = if precise { factor } else { 1.0 };
+        let applied = match (raw, state.anchor_raw, state.anchor_applied) {
+            (
+                GizmoDragDelta::Translation(raw),
+                GizmoDragDelta::Translation(anchor_raw),
+                GizmoDragDelta::Translation(anchor_applied),
+            ) => GizmoDragDelta::Translation(anchor_applied + (raw - anchor_raw) * scale),
+            (
+                GizmoDragDelta::Rotation(raw),
+                GizmoDragDelta::Rotation(anchor_raw),
+                GizmoDragDelta::Rotation(anchor_applied),
+            ) => GizmoDragDelta::Rotation(anchor_applied + (raw - anchor_raw) * scale),
+            _ => raw,
+        };
+        self.drag_delta = Some(applied);
+        applied
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PrecisionState {
+    precise: bool,
+    previous_raw: GizmoDragDelta,
+    // Raw and applied deltas at the moment the precision modifier was last toggled.
+    anchor_raw: GizmoDragDelta,
+    anchor_applied: GizmoDragDelta,
+}
+
+/// Total change applied to the selection since the start of a drag.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GizmoDragDelta {
+    /// World space translation.
+    Translation(Vec3),
+    /// Rotation angle in radians around the interaction's axis.
+    Rotation(f32),
+}
+
+impl GizmoDragDelta {
+    fn zero(self) -> Self {
+        match self {
+            GizmoDragDelta::Translation(_) => GizmoDragDelta::Translation(Vec3::ZERO),
+            GizmoDragDelta::Rotation(_) => GizmoDragDelta::Rotation(0.0),
+        }
+    }
 }
 
 /// Marks the current active gizmo interaction
 #[derive(Clone, Copy, Debug, PartialEq, Component)]
 pub enum TransformGizmoInteraction {
-    TranslateAxis { original: Vec3, axis: Vec3 },
-    TranslatePlane { original: Vec3, normal: Vec3 },
-    RotateAxis { original: Vec3, axis: Vec3 },
-    ScaleAxis { original: Vec3, axis: Vec3 },
+    TranslateAxis {
+        original: Vec3,
+        axis: Vec3,
+    },
+    TranslatePlane {
+        original: Vec3,
+        normal: Vec3,
+    },
+    RotateAxis {
+        original: Vec3,
+        axis: Vec3,
+    },
+    ScaleAxis {
+        original: Vec3,
+        axis: Vec3,
+    },
+    /// Dragging is handled by the [`CustomGizmoHandle`] of the handle.
+    Custom,
 }
 
 #[derive(Component)]
@@ -206,8 +606,8 @@ fn place_gizmo(
     let n_selected = selected.len();
     let transform_sum = selected.iter().fold(Vec3::ZERO, |acc, t| acc + *t);
     let centroid = transform_sum / n_selected as f32;
-    // Set the gizmo's position and visibility
-    if let Ok((mut g_transform, mut transform, mut visible)) = queries.p1().get_single_mut() {
+    // Set the position and visibility of the gizmo of each picking camera
+    for (mut g_transform, mut transform, mut visible) in &mut queries.p1() {
         let gt = g_transform.compute_transform();
         *g_transform = Transform {
             translation: centroid,
@@ -222,29 +622,120 @@ fn place_gizmo(
         } else {
             *visible = Visibility::Hidden;
         }
-    } else {
-        error!("Number of gizmos is != 1");
     }
 }
 
+/// Builds a gizmo and an overlay camera for every new picking camera, and removes them along with
+/// their picking camera.
+#[allow(clippy::too_many_arguments)]
+fn spawn_gizmos(
+    settings: Res<TransformGizmoSettings>,
+    new_cameras: Query<(Entity, Option<&GizmoOverlay>), Added<GizmoPickSource>>,
+    cameras: Query<(Option<&GizmoOverlay>, Option<&GizmoLayerOffset>), With<GizmoPickSource>>,
+    mut removed_cameras: RemovedComponents<GizmoPickSource>,
+    linked: Query<(Entity, &GizmoPickCamera)>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<GizmoMaterial>>,
+) {
+    let removed: Vec<Entity> = removed_cameras.read().collect();
+    for (entity, camera) in &linked {
+        if removed.contains(&camera.0) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    // Layers already taken by the gizmos of other cameras.
+    let mut used: Vec<u8> = cameras
+        .iter_many(linked.iter().map(|(_, camera)| camera.0))
+        .map(|(overlay, offset)| gizmo_render_layer(&settings, overlay, offset))
+        .collect();
+    for (camera, overlay) in &new_cameras {
+        let render_layer = match overlay {
+            Some(overlay) => {
+                if used.contains(&overlay.render_layer) {
+                    warn!(
+                        "Render layer {} of the gizmo overlay of {camera:?} is already used by \
+                        another gizmo, which will be shown and picked in both cameras.",
+                        overlay.render_layer
+                    );
+                }
+                overlay.render_layer
+            }
+            None => {
+                let free = (0..RenderLayers::TOTAL_LAYERS as u8)
+                    .map(|offset| (offset, settings.render_layer.saturating_add(offset)))
+                    .take_while(|(_, layer)| (*layer as usize) < RenderLayers::TOTAL_LAYERS)
+                    .find(|(_, layer)| !used.contains(layer));
+                let Some((offset, render_layer)) = free else {
+                    warn!("No free render layer left for the gizmo of {camera:?}.");
+                    continue;
+                };
+                commands.entity(camera).insert(GizmoLayerOffset(offset));
+                render_layer
+            }
+        };
+        used.push(render_layer);
+        mesh::build_gizmo(
+            camera,
+            render_layer,
+            &settings,
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+        );
+    }
+}
+
+/// Updates the global transforms of everything under the gizmos after they were placed, including
+/// decorations added by the app at any depth. Entities without a `Transform` are left alone, along
+/// with their descendants.
 fn propagate_gizmo_elements(
     gizmo: Query<(&GlobalTransform, &Children), With<TransformGizmo>>,
     mut gizmo_parts_query: Query<(&Transform, &mut GlobalTransform), Without<TransformGizmo>>,
+    children_query: Query<&Children, Without<TransformGizmo>>,
+) {
+    for (gizmo_pos, gizmo_parts) in &gizmo {
+        propagate_to_children(
+            gizmo_pos,
+            gizmo_parts,
+            &mut gizmo_parts_query,
+            &children_query,
+        );
+    }
+}
+
+fn propagate_to_children(
+    parent_transform: &GlobalTransform,
+    children: &Children,
+    gizmo_parts_query: &mut Query<(&Transform, &mut GlobalTransform), Without<TransformGizmo>>,
+    children_query: &Query<&Children, Without<TransformGizmo>>,
 ) {
-    if let Ok((gizmo_pos, gizmo_parts)) = gizmo.get_single() {
-        for &entity in gizmo_parts.iter() {
-            let (transform, mut g_transform) = gizmo_parts_query.get_mut(entity).unwrap();
-            *g_transform = gizmo_pos.mul_transform(*transform);
+    for &entity in children {
+        let Ok((transform, mut g_transform)) = gizmo_parts_query.get_mut(entity) else {
+            continue;
+        };
+        let global_transform = parent_transform.mul_transform(*transform);
+        *g_transform = global_transform;
+        if let Ok(grandchildren) = children_query.get(entity) {
+            propagate_to_children(
+                &global_transform,
+                grandchildren,
+                gizmo_parts_query,
+                children_query,
+            );
         }
     }
 }
 
 fn update_gizmo_settings(
     plugin_settings: Res<TransformGizmoSettings>,
+    new_gizmos: Query<(), Added<TransformGizmo>>,
     mut interactions: Query<&mut TransformGizmoInteraction, Without<ViewTranslateGizmo>>,
     mut rotations: Query<&mut Visibility, With<RotationGizmo>>,
+    mut labels: Query<(&AxisLabel, &mut Visibility), Without<RotationGizmo>>,
+    mut gizmo_cameras: Query<&mut Camera3d, With<InternalGizmoCamera>>,
 ) {
-    if !plugin_settings.is_changed() {
+    if !plugin_settings.is_changed() && new_gizmos.is_empty() {
         return;
     }
     let rotation = plugin_settings.alignment_rotation;
@@ -275,6 +766,7 @@ fn update_gizmo_settings(
                     axis: rotation.mul_vec3(original),
                 }
             }
+            TransformGizmoInteraction::Custom => TransformGizmoInteraction::Custom,
         };
     }
 
@@ -285,76 +777,395 @@ fn update_gizmo_settings(
             *visibility = Visibility::Hidden;
         }
     }
+
+    // In x-ray mode, keep the scene's depth so the gizmo is depth tested against it.
+    for mut camera_3d in &mut gizmo_cameras {
+        camera_3d.depth_load_op = match plugin_settings.depth_mode {
+            GizmoDepthMode::AlwaysOnTop => Camera3dDepthLoadOp::Clear(0.),
+            GizmoDepthMode::XRay { .. } => Camera3dDepthLoadOp::Load,
+        };
+    }
+
+    for (label, mut visibility) in &mut labels {
+        if label.style == plugin_settings.axis_labels {
+            *visibility = Visibility::Inherited;
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
 }
 
 #[allow(clippy::type_complexity)]
 fn adjust_view_translate_gizmo(
-    mut gizmo: Query<
+    gizmo: Query<(&GizmoPickCamera, &Children), With<TransformGizmo>>,
+    mut view_handles: Query<
         (&mut GlobalTransform, &mut TransformGizmoInteraction),
         (With<ViewTranslateGizmo>, Without<GizmoPickSource>),
     >,
-    camera: Query<&Transform, With<GizmoPickSource>>,
+    camera: Query<&GlobalTransform, With<GizmoPickSource>>,
 ) {
-    let (mut global_transform, mut interaction) = match gizmo.get_single_mut() {
-        Ok(x) => x,
-        Err(_) => return,
-    };
-
-    let cam_transform = match camera.get_single() {
-        Ok(x) => x,
-        Err(_) => return,
-    };
-
-    let direction = cam_transform.local_z();
-    *interaction = TransformGizmoInteraction::TranslatePlane {
-        original: Vec3::ZERO,
-        normal: direction,
-    };
-    let rotation = Quat::from_mat3(&Mat3::from_cols(
-        direction.cross(cam_transform.local_y()),
-        direction,
-        cam_transform.local_y(),
-    ));
-    *global_transform = Transform {
-        rotation,
-        ..global_transform.compute_transform()
-    }
-    .into();
+    for (pick_camera, children) in &gizmo {
+        let Ok(cam_transform) = camera.get(pick_camera.0) else {
+            continue;
+        };
+        let mut iter = view_handles.iter_many_mut(children);
+        while let Some((mut global_transform, mut interaction)) = iter.fetch_next() {
+            let direction = cam_transform.back();
+            *interaction = TransformGizmoInteraction::TranslatePlane {
+                original: Vec3::ZERO,
+                normal: direction,
+            };
+            let rotation = Quat::from_mat3(&Mat3::from_cols(
+                direction.cross(cam_transform.up()),
+                direction,
+                cam_transform.up(),
+            ));
+            *global_transform = Transform {
+                rotation,
+                ..global_transform.compute_transform()
+            }
+            .into();
+        }
+    }
+}
+
+/// Mirrors the translation handles and axis labels to the side of the gizmo facing the camera, if
+/// enabled in the settings.
+fn flip_handles_toward_camera(
+    settings: Res<TransformGizmoSettings>,
+    gizmo: Query<(&GizmoPickCamera, &GlobalTransform, &Children), With<TransformGizmo>>,
+    mut handles: Query<
+        (
+            &Transform,
+            &mut GlobalTransform,
+            Option<&TransformGizmoInteraction>,
+            Has<AxisLabel>,
+        ),
+        (Without<TransformGizmo>, Without<GizmoPickSource>),
+    >,
+    camera: Query<(&Camera, &GlobalTransform), With<GizmoPickSource>>,
+) {
+    if !settings.flip_handles_toward_camera {
+        return;
+    }
+    for (pick_camera, gizmo_transform, children) in &gizmo {
+        let Ok((camera, cam_transform)) = camera.get(pick_camera.0) else {
+            continue;
+        };
+        // Which side of each of the gizmo's local axes the camera is on.
+        let camera_local = gizmo_transform
+            .affine()
+            .inverse()
+            .transform_vector3(-view_direction(
+                camera,
+                cam_transform,
+                gizmo_transform.translation(),
+            ));
+        let sides = Vec3::select(camera_local.cmplt(Vec3::ZERO), Vec3::NEG_ONE, Vec3::ONE);
+        let mut iter = handles.iter_many_mut(children);
+        while let Some((transform, mut global_transform, interaction, is_label)) = iter.fetch_next()
+        {
+            let axis = match interaction {
+                Some(
+                    TransformGizmoInteraction::TranslateAxis { original, .. }
+                    | TransformGizmoInteraction::ScaleAxis { original, .. },
+                ) => Some(*original),
+                Some(TransformGizmoInteraction::TranslatePlane { original, .. })
+                    if *original != Vec3::ZERO =>
+                {
+                    None
+                }
+                None if is_label => None,
+                _ => continue,
+            };
+            let mut flipped = Transform {
+                translation: transform.translation * sides,
+                ..*transform
+            };
+            // Turn arrows around when their axis points away from the camera.
+            if let Some(axis) = axis.filter(|axis| axis.dot(sides) < 0.0) {
+                flipped.rotation = Quat::from_rotation_arc(axis, -axis) * flipped.rotation;
+            }
+            *global_transform = gizmo_transform.mul_transform(flipped);
+        }
+    }
+}
+
+/// Hides the translation axes pointing at the camera, if enabled in the settings.
+fn hide_camera_facing_handles(
+    settings: Res<TransformGizmoSettings>,
+    gizmo: Query<(
+        &TransformGizmo,
+        &GizmoPickCamera,
+        &GlobalTransform,
+        &Children,
+    )>,
+    mut handles: Query<(&TransformGizmoInteraction, &mut Visibility)>,
+    camera: Query<(&Camera, &GlobalTransform), With<GizmoPickSource>>,
+) {
+    for (gizmo, pick_camera, gizmo_transform, children) in &gizmo {
+        let Ok((camera, cam_transform)) = camera.get(pick_camera.0) else {
+            continue;
+        };
+        // Leave the handles alone mid-drag, the dragged axis may turn towards the camera.
+        if gizmo.current_interaction.is_some() {
+            continue;
+        }
+        let view_direction = view_direction(camera, cam_transform, gizmo_transform.translation());
+        let mut iter = handles.iter_many_mut(children);
+        while let Some((interaction, mut visibility)) = iter.fetch_next() {
+            let (TransformGizmoInteraction::TranslateAxis { axis, .. }
+            | TransformGizmoInteraction::ScaleAxis { axis, .. }) = *interaction
+            else {
+                continue;
+            };
+            let facing_camera =
+                view_direction.dot(axis.normalize()).abs() > settings.camera_facing_angle.cos();
+            if settings.hide_camera_facing_handles && facing_camera {
+                visibility.set_if_neq(Visibility::Hidden);
+            } else {
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+        }
+    }
+}
+
+/// Fades out the translation axes and planes, and rotation rings, seen end-on or edge-on, and stops
+/// them from being picked once fully faded out.
+fn fade_handles_by_view_angle(
+    settings: Res<TransformGizmoSettings>,
+    gizmo: Query<(
+        &TransformGizmo,
+        &GizmoPickCamera,
+        &GlobalTransform,
+        &Children,
+    )>,
+    handles: Query<(Entity, &TransformGizmoInteraction, Option<&HandleFade>)>,
+    camera: Query<(&Camera, &GlobalTransform), With<GizmoPickSource>>,
+    mut commands: Commands,
+) {
+    for (gizmo, pick_camera, gizmo_transform, children) in &gizmo {
+        let Ok((camera, cam_transform)) = camera.get(pick_camera.0) else {
+            continue;
+        };
+        // Keep the handles as they are mid-drag, so the dragged handle doesn't vanish.
+        if gizmo.current_interaction.is_some() {
+            continue;
+        }
+        let view_direction = view_direction(camera, cam_transform, gizmo_transform.translation());
+        for (entity, interaction, fade) in handles.iter_many(children) {
+            let opacity = match (settings.view_angle_fade, *interaction) {
+                (
+                    Some(view_angle_fade),
+                    TransformGizmoInteraction::TranslateAxis { axis, .. }
+                    | TransformGizmoInteraction::ScaleAxis { axis, .. },
+                ) => {
+                    let angle = view_direction.dot(axis.normalize()).abs().min(1.0).acos();
+                    view_angle_fade.opacity(angle)
+                }
+                (
+                    Some(view_angle_fade),
+                    TransformGizmoInteraction::TranslatePlane { original, normal },
+                ) if original != Vec3::ZERO => {
+                    let angle = view_direction.dot(normal.normalize()).abs().min(1.0).asin();
+                    view_angle_fade.opacity(angle)
+                }
+                // Rings lie in the plane normal to their axis.
+                (Some(view_angle_fade), TransformGizmoInteraction::RotateAxis { axis, .. }) => {
+                    let angle = view_direction.dot(axis.normalize()).abs().min(1.0).asin();
+                    view_angle_fade.opacity(angle)
+                }
+                _ => 1.0,
+            };
+            if fade.map_or(1.0, |fade| fade.0) != opacity {
+                let pickable = if opacity > 0.0 {
+                    Pickable::default()
+                } else {
+                    Pickable::IGNORE
+                };
+                commands
+                    .entity(entity)
+                    .insert((HandleFade(opacity), pickable));
+            }
+        }
+    }
+}
+
+/// Direction the camera sees `point` from. Orthographic cameras see everything straight ahead.
+fn view_direction(camera: &Camera, cam_transform: &GlobalTransform, point: Vec3) -> Vec3 {
+    if is_orthographic(camera) {
+        cam_transform.forward()
+    } else {
+        (point - cam_transform.translation()).normalize()
+    }
+}
+
+/// Whether the camera has an orthographic projection, whose clip space `w` doesn't depend on
+/// depth.
+fn is_orthographic(camera: &Camera) -> bool {
+    camera.projection_matrix().w_axis.w != 0.0
+}
+
+/// Turns the axis labels towards the camera so they can always be read.
+fn orient_axis_labels(
+    gizmo: Query<(&GizmoPickCamera, &Children), With<TransformGizmo>>,
+    mut labels: Query<&mut GlobalTransform, (With<AxisLabel>, Without<GizmoPickSource>)>,
+    camera: Query<&GlobalTransform, With<GizmoPickSource>>,
+) {
+    for (pick_camera, children) in &gizmo {
+        let Ok(cam_transform) = camera.get(pick_camera.0) else {
+            continue;
+        };
+        let (_, rotation, _) = cam_transform.to_scale_rotation_translation();
+        let mut iter = labels.iter_many_mut(children);
+        while let Some(mut global_transform) = iter.fetch_next() {
+            *global_transform = Transform {
+                rotation,
+                ..global_transform.compute_transform()
+            }
+            .into();
+        }
+    }
+}
+
+/// Moves each gizmo and its overlay camera to the render layer configured for their picking
+/// camera.
+fn update_render_layers(
+    settings: Res<TransformGizmoSettings>,
+    overlay: Query<(Option<&GizmoOverlay>, Option<&GizmoLayerOffset>), With<GizmoPickSource>>,
+    linked: Query<(Entity, &GizmoPickCamera)>,
+    children: Query<&Children>,
+    mut layers: Query<&mut RenderLayers>,
+) {
+    for (entity, pick_camera) in &linked {
+        let Ok((overlay, offset)) = overlay.get(pick_camera.0) else {
+            continue;
+        };
+        let render_layer = gizmo_render_layer(&settings, overlay, offset);
+        let render_layers = RenderLayers::layer(render_layer);
+        for entity in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+            if let Ok(mut layers) = layers.get_mut(entity) {
+                layers.set_if_neq(render_layers);
+            }
+        }
+    }
 }
 
 fn gizmo_cam_copy_settings(
+    settings: Res<TransformGizmoSettings>,
     main_cam: Query<
         (
             Ref<Camera>,
             Ref<GlobalTransform>,
             AnyOf<(Ref<Projection>, Ref<OrthographicProjection>)>,
+            Option<&GizmoOverlay>,
         ),
         With<GizmoPickSource>,
     >,
     mut gizmo_cam: Query<
-        (&mut Camera, &mut GlobalTransform, &mut Projection),
+        (
+            &GizmoPickCamera,
+            &mut Camera,
+            &mut GlobalTransform,
+            &mut Projection,
+        ),
         (With<InternalGizmoCamera>, Without<GizmoPickSource>),
     >,
 ) {
-    let Ok((main_cam, main_cam_pos, (main_proj, main_proj_ortho))) = main_cam.get_single() else {
+    if main_cam.is_empty() {
         error!("No GizmoPickSource found! Insert the GizmoPickSource component onto your primary camera.");
         return;
-    };
-    let (mut gizmo_cam, mut gizmo_cam_pos, mut proj) = gizmo_cam.single_mut();
-    if main_cam_pos.is_changed() {
-        *gizmo_cam_pos = *main_cam_pos;
-    }
-    if main_cam.is_changed() {
-        *gizmo_cam = main_cam.clone();
-        gizmo_cam.order += 10;
-    }
-    if let Some(main_proj) = main_proj {
-        if main_proj.is_changed() {
-            *proj = main_proj.clone();
+    }
+    for (pick_camera, mut gizmo_cam, mut gizmo_cam_pos, mut proj) in &mut gizmo_cam {
+        let Ok((main_cam, main_cam_pos, (main_proj, main_proj_ortho), overlay)) =
+            main_cam.get(pick_camera.0)
+        else {
+            continue;
+        };
+        // Overlay cameras spawned after their picking camera need a first copy as well.
+        let added = gizmo_cam.is_added();
+        if main_cam_pos.is_changed() || added {
+            *gizmo_cam_pos = *main_cam_pos;
+        }
+        if main_cam.is_changed() || added {
+            *gizmo_cam = main_cam.clone();
+        }
+        let order_offset = overlay.map_or(settings.camera_order_offset, |overlay| {
+            overlay.camera_order_offset
+        });
+        if gizmo_cam.order != main_cam.order + order_offset {
+            gizmo_cam.order = main_cam.order + order_offset;
+        }
+        if let Some(main_proj) = main_proj {
+            if main_proj.is_changed() || added {
+                *proj = main_proj.clone();
+            }
+        } else if let Some(main_proj_ortho) = main_proj_ortho {
+            if main_proj_ortho.is_changed() || added {
+                *proj = Projection::Orthographic(main_proj_ortho.clone());
+            }
         }
-    } else if let Some(main_proj_ortho) = main_proj_ortho {
-        if main_proj_ortho.is_changed() {
-            *proj = Projection::Orthographic(main_proj_ortho.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds `steps` of raw deltas to a fresh gizmo, toggling precision as given, and returns the
+    /// applied deltas.
+    fn apply_all(steps: &[(GizmoDragDelta, bool)]) -> Vec<GizmoDragDelta> {
+        let mut gizmo = TransformGizmo::default();
+        steps
+            .iter()
+            .map(|(raw, precise)| gizmo.apply_precision(*raw, *precise, 0.1))
+            .collect()
+    }
+
+    #[test]
+    fn precision_toggle_keeps_translation_continuous() {
+        let steps = [
+            (0.0, false),
+            (1.0, false),
+            (2.0, true),
+            (3.0, true),
+            (4.0, false),
+        ]
+        .map(|(x, precise)| (GizmoDragDelta::Translation(Vec3::X * x), precise));
+        let applied: Vec<f32> = apply_all(&steps)
+            .into_iter()
+            .map(|delta| match delta {
+                GizmoDragDelta::Translation(translation) => translation.x,
+                GizmoDragDelta::Rotation(_) => unreachable!(),
+            })
+            .collect();
+        let expected = [0.0, 1.0, 1.1, 1.2, 2.2];
+        for (applied, expected) in applied.iter().zip(expected) {
+            assert!((applied - expected).abs() < 1e-5, "{applied} != {expected}");
+        }
+    }
+
+    #[test]
+    fn precision_toggle_keeps_rotation_continuous_across_wrap() {
+        // The raw angle wraps from +π to -π while precision is on, and is released past it.
+        let steps = [(3.0, false), (3.1, true), (-3.1, true), (-3.0, false)]
+            .map(|(angle, precise)| (GizmoDragDelta::Rotation(angle), precise));
+        let applied: Vec<f32> = apply_all(&steps)
+            .into_iter()
+            .map(|delta| match delta {
+                GizmoDragDelta::Rotation(angle) => angle,
+                GizmoDragDelta::Translation(_) => unreachable!(),
+            })
+            .collect();
+        let wrapped_step = TAU - 6.2;
+        let expected = [
+            3.0,
+            3.0 + 0.1 * 0.1,
+            3.0 + 0.1 * (0.1 + wrapped_step),
+            3.0 + 0.1 * (0.1 + wrapped_step) + 0.1,
+        ];
+        for (applied, expected) in applied.iter().zip(expected) {
+            assert!((applied - expected).abs() < 1e-4, "{applied} != {expected}");
         }
     }
 }
@@ -0,0 +1,118 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+
+/// The outline drawn by a [`Glyph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlyphShape {
+    LetterX,
+    LetterY,
+    LetterZ,
+    Square,
+    Circle,
+    Triangle,
+}
+
+/// A flat glyph in the XY plane, centered on the origin and facing +Z.
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    pub shape: GlyphShape,
+    /// Width and height of the glyph.
+    pub size: f32,
+    /// Width of the strokes used to draw letters, relative to `size`.
+    pub stroke: f32,
+    pub subdivisions: usize,
+}
+
+impl Default for Glyph {
+    fn default() -> Self {
+        Glyph {
+            shape: GlyphShape::Square,
+            size: 1.0,
+            stroke: 0.2,
+            subdivisions: 24,
+        }
+    }
+}
+
+impl From<Glyph> for Mesh {
+    fn from(glyph: Glyph) -> Self {
+        let mut positions: Vec<[f32; 3]> = Vec::new();
+        let mut indices: Vec<u32> = Vec::new();
+
+        // Letters are made of straight strokes between points of the unit square.
+        let strokes: &[(Vec2, Vec2)] = match glyph.shape {
+            GlyphShape::LetterX => &[
+                (Vec2::new(-0.5, -0.5), Vec2::new(0.5, 0.5)),
+                (Vec2::new(-0.5, 0.5), Vec2::new(0.5, -0.5)),
+            ],
+            GlyphShape::LetterY => &[
+                (Vec2::new(-0.5, 0.5), Vec2::ZERO),
+                (Vec2::new(0.5, 0.5), Vec2::ZERO),
+                (Vec2::ZERO, Vec2::new(0.0, -0.5)),
+            ],
+            GlyphShape::LetterZ => &[
+                (Vec2::new(-0.5, 0.5), Vec2::new(0.5, 0.5)),
+                (Vec2::new(0.5, 0.5), Vec2::new(-0.5, -0.5)),
+                (Vec2::new(-0.5, -0.5), Vec2::new(0.5, -0.5)),
+            ],
+            _ => &[],
+        };
+        let half_stroke = glyph.stroke / 2.0;
+        for (start, end) in strokes {
+            let side = (*end - *start).normalize().perp() * half_stroke;
+            let first = positions.len() as u32;
+            for corner in [*start - side, *start + side, *end + side, *end - side] {
+                positions.push((corner * glyph.size).extend(0.0).into());
+            }
+            indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+        }
+
+        match glyph.shape {
+            GlyphShape::Square => {
+                for corner in [
+                    Vec2::new(-0.5, -0.5),
+                    Vec2::new(0.5, -0.5),
+                    Vec2::new(0.5, 0.5),
+                    Vec2::new(-0.5, 0.5),
+                ] {
+                    positions.push((corner * glyph.size).extend(0.0).into());
+                }
+                indices.extend([0, 1, 2, 0, 2, 3]);
+            }
+            GlyphShape::Circle => {
+                let stride = 2.0 * std::f32::consts::PI / glyph.subdivisions as f32;
+                positions.push([0.0, 0.0, 0.0]);
+                for side in 0..glyph.subdivisions {
+                    let phi = stride * side as f32;
+                    let corner = Vec2::new(phi.cos(), phi.sin()) * 0.5;
+                    positions.push((corner * glyph.size).extend(0.0).into());
+                    let next = (side + 1) % glyph.subdivisions;
+                    indices.extend([0, side as u32 + 1, next as u32 + 1]);
+                }
+            }
+            GlyphShape::Triangle => {
+                for corner in [
+                    Vec2::new(-0.5, -0.5),
+                    Vec2::new(0.5, -0.5),
+                    Vec2::new(0.0, 0.5),
+                ] {
+                    positions.push((corner * glyph.size).extend(0.0).into());
+                }
+                indices.extend([0, 1, 2]);
+            }
+            _ => (),
+        }
+
+        let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+        let uvs: Vec<[f32; 2]> = positions.iter().map(|p| [p[0], p[1]]).collect();
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh
+    }
+}
@@ -1,21 +1,32 @@
 use crate::{
-    gizmo_material::GizmoMaterial, GizmoPickSource, InitialTransform, InternalGizmoCamera,
-    PickableGizmo, TransformGizmo, TransformGizmoBundle, TransformGizmoInteraction,
+    custom_handle::{CustomGizmoHandle, CustomHandleDrag},
+    gizmo_material::GizmoMaterial,
+    picking::target_to_viewport,
+    shapes::AxisTip,
+    touch::{TouchHitArea, TouchHitMesh},
+    AxisLabels, GizmoControlEvent, GizmoDragDelta, GizmoPickCamera, GizmoPickSource,
+    InitialTransform, InternalGizmoCamera, PickableGizmo, TransformGizmo, TransformGizmoBundle,
+    TransformGizmoInteraction, TransformGizmoSettings,
 };
 use bevy::{
     core_pipeline::{clear_color::ClearColorConfig, core_3d::Camera3dDepthLoadOp},
+    ecs::system::SystemParam,
     pbr::NotShadowCaster,
     prelude::*,
-    render::view::RenderLayers,
+    render::{render_resource::PrimitiveTopology, view::NoFrustumCulling, view::RenderLayers},
 };
 use bevy_mod_picking::{
     events::{Drag, DragEnd, DragStart, Move, Out, Pointer},
-    prelude::{Listener, On},
+    prelude::{Listener, On, Pickable},
     selection::{NoDeselect, PickSelection},
 };
-use bevy_mod_raycast::{prelude::NoBackfaceCulling, primitives::Primitive3d};
+use bevy_mod_raycast::{
+    prelude::{NoBackfaceCulling, RaycastMethod},
+    primitives::Primitive3d,
+};
 
 mod cone;
+mod glyph;
 mod truncated_torus;
 
 #[derive(Component)]
@@ -24,295 +35,916 @@ pub struct RotationGizmo;
 #[derive(Component)]
 pub struct ViewTranslateGizmo;
 
+/// Opacity of a handle that is faded out because it is seen end-on or edge-on.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct HandleFade(pub f32);
+
+/// The filled sector showing the angle swept by a rotation drag.
+#[derive(Component)]
+pub struct RotationSweep;
+
+/// A glyph marking one of the gizmo axes, shown when `style` matches the current settings.
+#[derive(Component)]
+pub struct AxisLabel {
+    pub style: AxisLabels,
+}
+
+/// The handle hit by a pointer event, which owns the touch hit area when that was hit instead.
+fn hit_handle(target: Entity, hit_areas: &Query<&Parent, With<TouchHitArea>>) -> Entity {
+    hit_areas.get(target).map_or(target, |parent| parent.get())
+}
+
+// Pointer events bubble up to the listeners on the gizmo, which may be several levels above the
+// entity that was hit.
+
+fn on_hover(
+    event: Listener<Pointer<Move>>,
+    hit_areas: Query<&Parent, With<TouchHitArea>>,
+    handles: Query<(), With<TransformGizmoInteraction>>,
+    mut gizmo: Query<&mut TransformGizmo>,
+) {
+    let handle = hit_handle(event.target, &hit_areas);
+    if !handles.contains(handle) {
+        return;
+    }
+    if let Ok(mut gizmo) = gizmo.get_mut(event.listener()) {
+        if gizmo.hovered_handle != Some(handle) {
+            gizmo.hovered_handle = Some(handle);
+        }
+    }
+}
+
+fn on_hover_end(
+    event: Listener<Pointer<Out>>,
+    hit_areas: Query<&Parent, With<TouchHitArea>>,
+    mut gizmo: Query<&mut TransformGizmo>,
+) {
+    let handle = hit_handle(event.target, &hit_areas);
+    if let Ok(mut gizmo) = gizmo.get_mut(event.listener()) {
+        if gizmo.hovered_handle == Some(handle) {
+            gizmo.hovered_handle = None;
+        }
+    }
+}
+
+/// Colors the handles according to the palette. The hovered handle is highlighted, and while
+/// dragging the active handle stays highlighted and every other handle is faded out. Handles
+/// faded by their view angle are made transparent.
+pub fn update_handle_colors(
+    settings: Res<TransformGizmoSettings>,
+    gizmos: Query<(&TransformGizmo, &Children)>,
+    handles: Query<(
+        &TransformGizmoInteraction,
+        &Handle<GizmoMaterial>,
+        Option<&HandleFade>,
+    )>,
+    mut materials: ResMut<Assets<GizmoMaterial>>,
+) {
+    let palette = settings.palette;
+    for (gizmo, children) in &gizmos {
+        // Handles sharing a material, like the shaft and tip of an arrow, are highlighted together.
+        let material_of = |handle: Option<Entity>| {
+            handle
+                .and_then(|entity| handles.get(entity).ok())
+                .map(|(_, material, _)| material.id())
+        };
+        let active = material_of(gizmo.active_handle);
+        let hovered = material_of(gizmo.hovered_handle);
+        for (interaction, handle, fade) in handles.iter_many(children) {
+            let mut color = palette.handle_color(interaction);
+            if let Some(HandleFade(opacity)) = fade {
+                color.set_a(color.a() * opacity);
+            }
+            match active {
+                Some(active) if active == handle.id() => color = palette.highlight(color),
+                Some(_) => {
+                    color.set_a(color.a() * settings.inactive_handle_alpha);
+                }
+                None if hovered == Some(handle.id()) => color = palette.highlight(color),
+                None => (),
+            }
+            // Only write to changed materials, to avoid re-uploading them every frame.
+            if materials.get(handle).is_some_and(|m| m.color != color) {
+                materials.get_mut(handle).unwrap().color = color;
+            }
+        }
+    }
+}
+
 fn on_drag_start(
     event: Listener<Pointer<DragStart>>,
+    hit_areas: Query<&Parent, With<TouchHitArea>>,
     selected_items_query: Query<(&PickSelection, &GlobalTransform, Entity)>,
-    parents: Query<(&TransformGizmoInteraction, &Parent)>,
+    handles: Query<&TransformGizmoInteraction>,
     mut gizmo: Query<(&GlobalTransform, &mut TransformGizmo)>,
     mut commands: Commands,
 ) {
-    // Dragging has started, store the initial position of all selected meshes
-    for (selection, transform, entity) in &selected_items_query {
-        if selection.is_selected {
-            commands.entity(entity).insert(InitialTransform {
-                transform: transform.compute_transform(),
-            });
-        }
-    }
-
-    let Ok((t, parent)) = parents.get(event.target) else {
+    let handle = hit_handle(event.target, &hit_areas);
+    // Other children of the gizmo, such as decorations, can't be dragged.
+    let Ok(interaction) = handles.get(handle) else {
         return;
     };
-
-    let (transform, mut gizmo) = gizmo.get_mut(parent.get()).unwrap();
-
-    gizmo.initial_transform = Some(*transform);
-    gizmo.current_interaction = Some(*t);
+    let Ok((transform, mut gizmo)) = gizmo.get_mut(event.listener()) else {
+        return;
+    };
+    store_initial_transforms(&selected_items_query, &mut commands);
+    gizmo.begin_drag(*transform, *interaction, handle, Some(event.pointer_id));
 }
 
 fn on_drag_end(
     event: Listener<Pointer<DragEnd>>,
     selected_items_query: Query<Entity, With<InitialTransform>>,
-    parents: Query<&Parent>,
     mut gizmo: Query<&mut TransformGizmo>,
     mut commands: Commands,
 ) {
-    // Dragging has started, store the initial position of all selected meshes
-    for entity in &selected_items_query {
-        commands.entity(entity).remove::<InitialTransform>();
-    }
-
-    let Ok(parent) = parents.get(event.target) else {
+    let Ok(mut gizmo) = gizmo.get_mut(event.listener()) else {
         return;
     };
+    // Only the pointer that grabbed a handle ends its drag.
+    if gizmo.pointer != Some(event.pointer_id) {
+        return;
+    }
+    clear_initial_transforms(&selected_items_query, &mut commands);
+    gizmo.end_drag();
+}
 
-    let mut gizmo = gizmo.get_mut(parent.get()).unwrap();
+/// Dragging has started, store the initial position of all selected meshes.
+fn store_initial_transforms(
+    selected_items_query: &Query<(&PickSelection, &GlobalTransform, Entity)>,
+    commands: &mut Commands,
+) {
+    for (selection, transform, entity) in selected_items_query {
+        if selection.is_selected {
+            commands.entity(entity).insert(InitialTransform {
+                transform: transform.compute_transform(),
+            });
+        }
+    }
+}
 
-    gizmo.initial_transform = None;
-    gizmo.drag_start = None;
-    gizmo.current_interaction = None;
+/// Dragging has ended, forget the initial position of the selected meshes.
+fn clear_initial_transforms(
+    selected_items_query: &Query<Entity, With<InitialTransform>>,
+    commands: &mut Commands,
+) {
+    for entity in selected_items_query {
+        commands.entity(entity).remove::<InitialTransform>();
+    }
 }
 
-fn on_drag(
-    event: Listener<Pointer<Drag>>,
+/// Hovers, grabs and releases handles as requested by [`GizmoControlEvent`]s.
+pub fn apply_control_events(
+    mut events: EventReader<GizmoControlEvent>,
+    handles: Query<&TransformGizmoInteraction>,
     parents: Query<&Parent>,
     mut gizmo: Query<(&GlobalTransform, &mut TransformGizmo)>,
-    pick_cam: Query<&GizmoPickSource>,
+    selected_items_query: Query<(&PickSelection, &GlobalTransform, Entity)>,
+    stored_items_query: Query<Entity, With<InitialTransform>>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        match *event {
+            GizmoControlEvent::Hover {
+                gizmo: entity,
+                handle,
+            } => {
+                if let Ok((_, mut gizmo)) = gizmo.get_mut(entity) {
+                    if gizmo.hovered_handle != handle {
+                        gizmo.hovered_handle = handle;
+                    }
+                }
+            }
+            GizmoControlEvent::Grab { handle } => {
+                let Ok(interaction) = handles.get(handle) else {
+                    continue;
+                };
+                let Some((transform, mut gizmo)) = parents
+                    .iter_ancestors(handle)
+                    .find(|entity| gizmo.contains(*entity))
+                    .and_then(|entity| gizmo.get_mut(entity).ok())
+                else {
+                    continue;
+                };
+                if gizmo.current_interaction.is_none() {
+                    store_initial_transforms(&selected_items_query, &mut commands);
+                    gizmo.begin_drag(*transform, *interaction, handle, None);
+                }
+            }
+            GizmoControlEvent::Release { gizmo: entity } => {
+                let Ok((_, mut gizmo)) = gizmo.get_mut(entity) else {
+                    continue;
+                };
+                if gizmo.current_interaction.is_some() && gizmo.pointer.is_none() {
+                    clear_initial_transforms(&stored_items_query, &mut commands);
+                    gizmo.end_drag();
+                }
+            }
+        }
+    }
+}
+
+/// Maps cursor movement to a drag, for drags that can't be computed by intersecting the picking
+/// ray with a plane because the plane is nearly edge-on to the camera.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScreenSpaceDrag {
+    /// Moving the cursor along `direction` moves the selection along the dragged axis.
+    Axis {
+        start_cursor: Vec2,
+        direction: Vec2,
+        world_per_pixel: f32,
+    },
+    /// Moving the cursor horizontally and vertically moves the selection along `right` and `up`,
+    /// which lie in the dragged plane.
+    Plane {
+        start_cursor: Vec2,
+        right: Vec3,
+        up: Vec3,
+        world_per_pixel: f32,
+    },
+    /// Moving the cursor around the projected gizmo origin rotates the selection. `sign` maps
+    /// counter-clockwise motion on the screen to the direction of the rotation axis.
+    Rotation {
+        start_cursor: Vec2,
+        pivot: Vec2,
+        sign: f32,
+    },
+}
+
+impl ScreenSpaceDrag {
+    fn axis(
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        origin: Vec3,
+        cursor: Vec2,
+        direction: Vec2,
+    ) -> Option<Self> {
+        Some(ScreenSpaceDrag::Axis {
+            start_cursor: cursor,
+            direction: direction.normalize(),
+            world_per_pixel: world_per_pixel(camera, camera_transform, origin)?,
+        })
+    }
 
-    mut transform_query: Query<
+    fn plane(
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        origin: Vec3,
+        cursor: Vec2,
+        normal: Vec3,
+    ) -> Option<Self> {
+        // Use the camera's right and up directions projected onto the plane. When one of them is
+        // nearly perpendicular to the plane, moving the cursor along it pushes the selection away
+        // from the camera instead.
+        let project = |v: Vec3| v - normal * v.dot(normal);
+        let away = project(camera_transform.forward()).normalize_or_zero();
+        let in_plane = |v: Vec3| {
+            let projected = project(v);
+            if projected.length() < 0.5 {
+                away
+            } else {
+                projected.normalize()
+            }
+        };
+        Some(ScreenSpaceDrag::Plane {
+            start_cursor: cursor,
+            right: in_plane(camera_transform.right()),
+            up: in_plane(camera_transform.up()),
+            world_per_pixel: world_per_pixel(camera, camera_transform, origin)?,
+        })
+    }
+
+    fn rotation(
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        origin: Vec3,
+        cursor: Vec2,
+        sign: f32,
+    ) -> Option<Self> {
+        Some(ScreenSpaceDrag::Rotation {
+            start_cursor: cursor,
+            pivot: camera.world_to_viewport(camera_transform, origin)?,
+            sign,
+        })
+    }
+
+    /// Distance in world units for axis drags.
+    fn distance(&self, cursor: Vec2) -> f32 {
+        match *self {
+            ScreenSpaceDrag::Axis {
+                start_cursor,
+                direction,
+                world_per_pixel,
+            } => (cursor - start_cursor).dot(direction) * world_per_pixel,
+            _ => 0.0,
+        }
+    }
+
+    /// World space translation in the dragged plane for plane drags.
+    fn translation(&self, cursor: Vec2) -> Vec3 {
+        match *self {
+            ScreenSpaceDrag::Plane {
+                start_cursor,
+                right,
+                up,
+                world_per_pixel,
+            } => {
+                // Viewport coordinates grow downwards.
+                let delta = cursor - start_cursor;
+                (right * delta.x - up * delta.y) * world_per_pixel
+            }
+            _ => Vec3::ZERO,
+        }
+    }
+
+    /// Angle in radians around the rotation axis for rotation drags.
+    fn angle(&self, cursor: Vec2) -> f32 {
+        match *self {
+            ScreenSpaceDrag::Rotation {
+                start_cursor,
+                pivot,
+                sign,
+            } => {
+                let from = start_cursor - pivot;
+                let to = cursor - pivot;
+                // Viewport coordinates grow downwards, which flips the winding.
+                -from.perp_dot(to).atan2(from.dot(to)) * sign
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// Size in world units of a pixel at the depth of `point`.
+fn world_per_pixel(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    point: Vec3,
+) -> Option<f32> {
+    let root = camera.world_to_viewport(camera_transform, point)?;
+    let end = camera.world_to_viewport(camera_transform, point + camera_transform.right())?;
+    let pixels_per_world = root.distance(end);
+    (pixels_per_world > 0.0).then(|| pixels_per_world.recip())
+}
+
+/// Fraction of the far plane distance the selection can be dragged to.
+const FAR_PLANE_MARGIN: f32 = 0.99;
+
+/// Limits a drag translation to the configured maximum distance, and keeps the selection in front
+/// of the camera's far plane so it can't be dragged out of view. Plane drags, given the
+/// `plane_normal`, only lose the excess depth and keep sliding sideways along the far plane.
+fn clamp_translation(
+    settings: &TransformGizmoSettings,
+    camera_transform: &GlobalTransform,
+    far: f32,
+    origin: Vec3,
+    translation: Vec3,
+    plane_normal: Option<Vec3>,
+) -> Vec3 {
+    let translation = match settings.max_drag_distance {
+        Some(max) => translation.clamp_length_max(max),
+        None => translation,
+    };
+    let forward = camera_transform.forward();
+    let depth = (origin - camera_transform.translation()).dot(forward);
+    let max_depth_change = (far * FAR_PLANE_MARGIN - depth).max(0.0);
+    let depth_change = translation.dot(forward);
+    if depth_change <= max_depth_change {
+        return translation;
+    }
+    // The direction within the plane that goes deepest the fastest.
+    let deepest = plane_normal
+        .map(|normal| forward - normal * forward.dot(normal))
+        .filter(|deepest| deepest.length_squared() > f32::EPSILON);
+    match deepest {
+        Some(deepest) => {
+            translation - deepest * (depth_change - max_depth_change) / deepest.length_squared()
+        }
+        None => translation * (max_depth_change / depth_change),
+    }
+}
+
+fn on_drag(event: Listener<Pointer<Drag>>, mut drag: DragGizmo) {
+    drag.drag(event.listener());
+}
+
+/// Drags the gizmos grabbed with a [`GizmoControlEvent`], which don't get pointer events.
+pub fn drag_controlled_gizmos(mut drag: DragGizmo) {
+    let controlled: Vec<Entity> = drag
+        .gizmo
+        .iter()
+        .filter(|(_, _, gizmo, _)| gizmo.current_interaction.is_some() && gizmo.pointer.is_none())
+        .map(|(entity, ..)| entity)
+        .collect();
+    for entity in controlled {
+        drag.drag(entity);
+    }
+}
+
+/// Everything needed to move the selection along with a dragged gizmo.
+#[derive(SystemParam)]
+pub struct DragGizmo<'w, 's> {
+    settings: Res<'w, TransformGizmoSettings>,
+    keys: Res<'w, Input<KeyCode>>,
+    gizmo: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static GlobalTransform,
+            &'static mut TransformGizmo,
+            &'static GizmoPickCamera,
+        ),
+    >,
+    pick_cam: Query<
+        'w,
+        's,
         (
-            &PickSelection,
-            Option<&Parent>,
-            &mut Transform,
-            &InitialTransform,
+            &'static GizmoPickSource,
+            &'static Camera,
+            &'static GlobalTransform,
+            AnyOf<(&'static Projection, &'static OrthographicProjection)>,
+        ),
+    >,
+    transform_query: Query<
+        'w,
+        's,
+        (
+            &'static PickSelection,
+            Option<&'static Parent>,
+            &'static mut Transform,
+            &'static InitialTransform,
         ),
         Without<TransformGizmo>,
     >,
-    // cameras: Query<(&Camera, &GlobalTransform)>,
-    global_transforms: Query<&GlobalTransform>,
-) {
-    let Ok(picking_camera) = pick_cam.get_single() else {
-        return; // Not exactly one picking camera.
-    };
-    let Some(picking_ray) = picking_camera.get_ray() else {
-        return; // Picking camera does not have a ray.
-    };
-    // let (camera, camera_transform) = cameras.get(event.).unwrap();
+    global_transforms: Query<'w, 's, &'static GlobalTransform>,
+    custom_handles: Query<'w, 's, &'static CustomGizmoHandle>,
+}
 
-    let (gizmo_transform, mut gizmo) = gizmo
-        .get_mut(parents.get(event.target).unwrap().get())
-        .unwrap();
+impl DragGizmo<'_, '_> {
+    /// Moves the selection according to the picking ray of the camera of `gizmo`.
+    fn drag(&mut self, gizmo: Entity) {
+        let settings = &self.settings;
+        let keys = &self.keys;
+        let global_transforms = &self.global_transforms;
+        let Ok((_, gizmo_transform, mut gizmo, pick_camera)) = self.gizmo.get_mut(gizmo) else {
+            return;
+        };
+        let Ok((picking_camera, camera, camera_transform, projection)) =
+            self.pick_cam.get(pick_camera.0)
+        else {
+            return; // The picking camera of this gizmo is gone.
+        };
+        let far = match projection {
+            (Some(Projection::Perspective(projection)), _) => projection.far,
+            (Some(Projection::Orthographic(projection)), _) | (None, Some(projection)) => {
+                projection.far
+            }
+            (None, None) => f32::INFINITY,
+        };
+        let Some(picking_ray) = picking_camera.get_ray() else {
+            return; // Picking camera does not have a ray.
+        };
+        let cursor = match picking_camera.cast_method {
+            RaycastMethod::Screenspace(cursor) => target_to_viewport(camera, cursor),
+            _ => None,
+        };
 
-    let Some(gizmo_origin) = gizmo.initial_transform.map(|t| t.translation()) else {
-        return;
-    };
+        let Some(gizmo_origin) = gizmo.initial_transform.map(|t| t.translation()) else {
+            return;
+        };
+        let precise = settings
+            .precision_modifiers
+            .iter()
+            .any(|key| keys.pressed(*key));
 
-    let selected_iter = transform_query
-        .iter_mut()
-        .filter(|(s, ..)| s.is_selected)
-        .map(|(_, parent, local_transform, initial_global_transform)| {
-            let parent_global_transform = parent
-                .and_then(|parent| global_transforms.get(parent.get()).ok())
-                .unwrap_or(&GlobalTransform::IDENTITY);
-            let parent_mat = parent_global_transform.compute_matrix();
-            let inverse_parent = parent_mat.inverse();
-            (inverse_parent, local_transform, initial_global_transform)
-        });
+        let selected_iter = self
+            .transform_query
+            .iter_mut()
+            .filter(|(s, ..)| s.is_selected)
+            .map(|(_, parent, local_transform, initial_global_transform)| {
+                let parent_global_transform = parent
+                    .and_then(|parent| global_transforms.get(parent.get()).ok())
+                    .unwrap_or(&GlobalTransform::IDENTITY);
+                let parent_mat = parent_global_transform.compute_matrix();
+                let inverse_parent = parent_mat.inverse();
+                (inverse_parent, local_transform, initial_global_transform)
+            });
 
-    if let Some(interaction) = gizmo.current_interaction {
-        if gizmo.initial_transform.is_none() {
-            gizmo.initial_transform = Some(*gizmo_transform);
-        }
-        match interaction {
-            TransformGizmoInteraction::TranslateAxis { original: _, axis } => {
-                let vertical_vector = picking_ray.direction().cross(axis).normalize();
-                let plane_normal = axis.cross(vertical_vector).normalize();
-                let plane_origin = gizmo_origin;
-                let cursor_plane_intersection = if let Some(intersection) = picking_camera
-                    .intersect_primitive(Primitive3d::Plane {
-                        normal: plane_normal,
-                        point: plane_origin,
-                    }) {
-                    intersection.position()
-                } else {
-                    return;
-                };
-                let cursor_vector: Vec3 = cursor_plane_intersection - plane_origin;
-                let Some(cursor_projected_onto_handle) = gizmo.drag_start else {
-                    let handle_vector = axis;
-                    let cursor_projected_onto_handle =
-                        cursor_vector.dot(handle_vector.normalize()) * handle_vector.normalize();
-                    gizmo.drag_start = Some(cursor_projected_onto_handle + plane_origin);
-                    return;
-                };
-                let selected_handle_vec = cursor_projected_onto_handle - plane_origin;
-                let new_handle_vec = cursor_vector.dot(selected_handle_vec.normalize())
-                    * selected_handle_vec.normalize();
-                let translation = new_handle_vec - selected_handle_vec;
-                selected_iter.for_each(
-                    |(inverse_parent, mut local_transform, initial_global_transform)| {
-                        let new_transform = Transform {
-                            translation: initial_global_transform.transform.translation
-                                + translation,
-                            rotation: initial_global_transform.transform.rotation,
-                            scale: initial_global_transform.transform.scale,
-                        };
-                        let local = inverse_parent * new_transform.compute_matrix();
-                        local_transform.set_if_neq(Transform::from_matrix(local));
-                    },
-                );
+        if let Some(interaction) = gizmo.current_interaction {
+            if gizmo.initial_transform.is_none() {
+                gizmo.initial_transform = Some(*gizmo_transform);
             }
-            TransformGizmoInteraction::TranslatePlane { normal, .. } => {
-                let plane_origin = gizmo_origin;
-                let cursor_plane_intersection = if let Some(intersection) = picking_camera
-                    .intersect_primitive(Primitive3d::Plane {
-                        normal,
-                        point: plane_origin,
-                    }) {
-                    intersection.position()
-                } else {
-                    return;
-                };
-                let Some(drag_start) = gizmo.drag_start else {
-                    gizmo.drag_start = Some(cursor_plane_intersection);
-                    return; // We just started dragging, no transformation is needed yet, exit early.
-                };
-                selected_iter.for_each(
-                    |(inverse_parent, mut local_transform, initial_transform)| {
-                        let new_transform = Transform {
-                            translation: initial_transform.transform.translation
-                                + cursor_plane_intersection
-                                - drag_start,
-                            rotation: initial_transform.transform.rotation,
-                            scale: initial_transform.transform.scale,
+            match interaction {
+                TransformGizmoInteraction::TranslateAxis { original: _, axis } => {
+                    let axis = axis.normalize();
+                    let facing_camera = picking_ray.direction().dot(axis).abs()
+                        > settings.camera_facing_angle.cos();
+                    if gizmo.drag_start.is_none() && facing_camera {
+                        // The drag plane below degenerates when the axis is nearly parallel to the
+                        // view ray, so drag in screen space instead: moving the cursor up moves the
+                        // selection away from the camera.
+                        let away = picking_ray.direction().dot(axis).signum();
+                        if let Some(screen_drag) = cursor.and_then(|cursor| {
+                            ScreenSpaceDrag::axis(
+                                camera,
+                                camera_transform,
+                                gizmo_origin,
+                                cursor,
+                                Vec2::NEG_Y * away,
+                            )
+                        }) {
+                            gizmo.screen_drag = Some(screen_drag);
+                            gizmo.drag_start = Some(gizmo_origin);
+                            return;
+                        }
+                    }
+                    let raw_translation = if let Some(screen_drag) = gizmo.screen_drag {
+                        let Some(cursor) = cursor else {
+                            return;
                         };
+                        axis * screen_drag.distance(cursor)
+                    } else {
+                        let vertical_vector = picking_ray.direction().cross(axis).normalize();
+                        let plane_normal = axis.cross(vertical_vector).normalize();
+                        let plane_origin = gizmo_origin;
+                        let cursor_plane_intersection = if let Some(intersection) = picking_camera
+                            .intersect_primitive(Primitive3d::Plane {
+                                normal: plane_normal,
+                                point: plane_origin,
+                            }) {
+                            intersection.position()
+                        } else {
+                            return;
+                        };
+                        let cursor_vector: Vec3 = cursor_plane_intersection - plane_origin;
+                        let Some(cursor_projected_onto_handle) = gizmo.drag_start else {
+                            let cursor_projected_onto_handle = cursor_vector.dot(axis) * axis;
+                            gizmo.drag_start = Some(cursor_projected_onto_handle + plane_origin);
+                            return;
+                        };
+                        let selected_handle_vec = cursor_projected_onto_handle - plane_origin;
+                        axis * (cursor_vector - selected_handle_vec).dot(axis)
+                    };
+                    let raw_translation = clamp_translation(
+                        settings,
+                        camera_transform,
+                        far,
+                        gizmo_origin,
+                        raw_translation,
+                        None,
+                    );
+                    let GizmoDragDelta::Translation(translation) = gizmo.apply_precision(
+                        GizmoDragDelta::Translation(raw_translation),
+                        precise,
+                        settings.precision_factor,
+                    ) else {
+                        return;
+                    };
+                    selected_iter.for_each(
+                        |(inverse_parent, mut local_transform, initial_global_transform)| {
+                            let new_transform = Transform {
+                                translation: initial_global_transform.transform.translation
+                                    + translation,
+                                rotation: initial_global_transform.transform.rotation,
+                                scale: initial_global_transform.transform.scale,
+                            };
+                            let local = inverse_parent * new_transform.compute_matrix();
+                            local_transform.set_if_neq(Transform::from_matrix(local));
+                        },
+                    );
+                }
+                TransformGizmoInteraction::TranslatePlane { normal, .. } => {
+                    let normal = normal.normalize();
+                    let edge_on = picking_ray.direction().dot(normal).abs()
+                        < settings.camera_facing_angle.sin();
+                    if gizmo.drag_start.is_none() && edge_on {
+                        // The intersection with a plane seen edge-on jumps around, map the cursor
+                        // movement onto the plane instead.
+                        if let Some(screen_drag) = cursor.and_then(|cursor| {
+                            ScreenSpaceDrag::plane(
+                                camera,
+                                camera_transform,
+                                gizmo_origin,
+                                cursor,
+                                normal,
+                            )
+                        }) {
+                            gizmo.screen_drag = Some(screen_drag);
+                            gizmo.drag_start = Some(gizmo_origin);
+                            return;
+                        }
+                    }
+                    let raw_translation = if let Some(screen_drag) = gizmo.screen_drag {
+                        let Some(cursor) = cursor else {
+                            return;
+                        };
+                        screen_drag.translation(cursor)
+                    } else {
+                        let plane_origin = gizmo_origin;
+                        let cursor_plane_intersection = if let Some(intersection) = picking_camera
+                            .intersect_primitive(Primitive3d::Plane {
+                                normal,
+                                point: plane_origin,
+                            }) {
+                            intersection.position()
+                        } else {
+                            return;
+                        };
+                        let Some(drag_start) = gizmo.drag_start else {
+                            gizmo.drag_start = Some(cursor_plane_intersection);
+                            return; // We just started dragging, no transformation is needed yet, exit early.
+                        };
+                        cursor_plane_intersection - drag_start
+                    };
+                    let GizmoDragDelta::Translation(translation) = gizmo.apply_precision(
+                        GizmoDragDelta::Translation(clamp_translation(
+                            settings,
+                            camera_transform,
+                            far,
+                            gizmo_origin,
+                            raw_translation,
+                            Some(normal),
+                        )),
+                        precise,
+                        settings.precision_factor,
+                    ) else {
+                        return;
+                    };
+                    selected_iter.for_each(
+                        |(inverse_parent, mut local_transform, initial_transform)| {
+                            let new_transform = Transform {
+                                translation: initial_transform.transform.translation + translation,
+                                rotation: initial_transform.transform.rotation,
+                                scale: initial_transform.transform.scale,
+                            };
+                            let local = inverse_parent * new_transform.compute_matrix();
+                            local_transform.set_if_neq(Transform::from_matrix(local));
+                        },
+                    );
+                }
+                TransformGizmoInteraction::RotateAxis { original: _, axis } => {
+                    let axis = axis.normalize();
+                    let rotation_plane = Primitive3d::Plane {
+                        normal: axis,
+                        point: gizmo_origin,
+                    };
+                    let intersection = picking_camera.intersect_primitive(rotation_plane);
+                    let edge_on = picking_ray.direction().dot(axis).abs()
+                        < settings.camera_facing_angle.sin();
+                    if gizmo.drag_start.is_none() && (edge_on || intersection.is_none()) {
+                        // Rotate with the cursor's angle around the projected gizmo origin instead.
+                        // Counter-clockwise on screen is a positive rotation when the axis points at
+                        // the camera.
+                        let sign = if picking_ray.direction().dot(axis) > 0.0 {
+                            -1.0
+                        } else {
+                            1.0
+                        };
+                        if let Some(screen_drag) = cursor.and_then(|cursor| {
+                            ScreenSpaceDrag::rotation(
+                                camera,
+                                camera_transform,
+                                gizmo_origin,
+                                cursor,
+                                sign,
+                            )
+                        }) {
+                            // Start the rotation sweep from the direction facing the camera.
+                            let towards_camera = -picking_ray.direction();
+                            let start = (towards_camera - axis * towards_camera.dot(axis))
+                                .try_normalize()
+                                .unwrap_or_else(|| axis.any_orthonormal_vector());
+                            gizmo.screen_drag = Some(screen_drag);
+                            gizmo.drag_start = Some(start);
+                            return;
+                        }
+                    }
+                    let raw_angle = if let Some(screen_drag) = gizmo.screen_drag {
+                        let Some(cursor) = cursor else {
+                            return;
+                        };
+                        screen_drag.angle(cursor)
+                    } else {
+                        let Some(intersection) = intersection else {
+                            return;
+                        };
+                        let cursor_vector = (intersection.position() - gizmo_origin).normalize();
+                        let Some(drag_start) = gizmo.drag_start else {
+                            gizmo.drag_start = Some(cursor_vector);
+                            return; // We just started dragging, no transformation is needed yet, exit early.
+                        };
+                        let dot = drag_start.dot(cursor_vector);
+                        let det = axis.dot(drag_start.cross(cursor_vector));
+                        det.atan2(dot)
+                    };
+                    let GizmoDragDelta::Rotation(angle) = gizmo.apply_precision(
+                        GizmoDragDelta::Rotation(raw_angle),
+                        precise,
+                        settings.precision_factor,
+                    ) else {
+                        return;
+                    };
+                    let rotation = Quat::from_axis_angle(axis, angle);
+                    selected_iter.for_each(
+                        |(inverse_parent, mut local_transform, initial_transform)| {
+                            let mut new_transform = initial_transform.transform;
+                            new_transform.rotate_around(gizmo_origin, rotation);
+                            let local = inverse_parent * new_transform.compute_matrix();
+                            local_transform.set_if_neq(Transform::from_matrix(local));
+                        },
+                    );
+                }
+                TransformGizmoInteraction::ScaleAxis {
+                    original: _,
+                    axis: _,
+                } => (),
+                TransformGizmoInteraction::Custom => {
+                    let Some(CustomGizmoHandle(handle)) = gizmo
+                        .active_handle
+                        .and_then(|entity| self.custom_handles.get(entity).ok())
+                    else {
+                        return;
+                    };
+                    let ray = Ray {
+                        origin: picking_ray.origin(),
+                        direction: picking_ray.direction(),
+                    };
+                    let Some(start_ray) = gizmo.start_ray else {
+                        gizmo.start_ray = Some(ray);
+                        return;
+                    };
+                    let (selected, initial_transforms): (Vec<_>, Vec<_>) = selected_iter
+                        .map(|(inverse_parent, local_transform, initial_transform)| {
+                            (
+                                (inverse_parent, local_transform),
+                                initial_transform.transform,
+                            )
+                        })
+                        .unzip();
+                    let new_transforms = handle.drag(&CustomHandleDrag {
+                        ray,
+                        start_ray,
+                        gizmo_origin,
+                        initial_transforms: &initial_transforms,
+                    });
+                    for ((inverse_parent, mut local_transform), new_transform) in
+                        selected.into_iter().zip(new_transforms)
+                    {
                         let local = inverse_parent * new_transform.compute_matrix();
                         local_transform.set_if_neq(Transform::from_matrix(local));
-                    },
-                );
-            }
-            TransformGizmoInteraction::RotateAxis { original: _, axis } => {
-                let rotation_plane = Primitive3d::Plane {
-                    normal: axis.normalize(),
-                    point: gizmo_origin,
-                };
-                let cursor_plane_intersection = if let Some(intersection) =
-                    picking_camera.intersect_primitive(rotation_plane)
-                {
-                    intersection.position()
-                } else {
-                    return;
-                };
-                let cursor_vector = (cursor_plane_intersection - gizmo_origin).normalize();
-                let Some(drag_start) = gizmo.drag_start else {
-                    gizmo.drag_start = Some(cursor_vector);
-                    return; // We just started dragging, no transformation is needed yet, exit early.
-                };
-                let dot = drag_start.dot(cursor_vector);
-                let det = axis.dot(drag_start.cross(cursor_vector));
-                let angle = det.atan2(dot);
-                let rotation = Quat::from_axis_angle(axis, angle);
-                selected_iter.for_each(
-                    |(inverse_parent, mut local_transform, initial_transform)| {
-                        let mut new_transform = initial_transform.transform;
-                        new_transform.rotate_around(gizmo_origin, rotation);
-                        let local = inverse_parent * new_transform.compute_matrix();
-                        local_transform.set_if_neq(Transform::from_matrix(local));
-                    },
-                );
+                    }
+                }
             }
-            TransformGizmoInteraction::ScaleAxis {
-                original: _,
-                axis: _,
-            } => (),
         }
     }
 }
 
-/// Startup system that builds the procedural mesh and materials of the gizmo.
+/// How much larger the touch hit areas of the plane handles are than the handles, small enough
+/// not to reach over the handles next to them.
+const PLANE_HIT_MARGIN: f32 = 1.1;
+
+/// Spawns a gizmo and the overlay camera drawing it for the picking camera `pick_camera`.
 pub fn build_gizmo(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<GizmoMaterial>>,
+    pick_camera: Entity,
+    render_layer: u8,
+    settings: &TransformGizmoSettings,
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<GizmoMaterial>,
 ) {
-    let axis_length = 1.3;
-    let arc_radius = 1.;
-    let plane_size = axis_length * 0.35;
-    let plane_offset = plane_size / 2.;
-    // Define gizmo meshes
-    let arrow_tail_mesh = meshes.add(Mesh::from(shape::Cylinder {
-        radius: 0.04,
+    let shapes = &settings.shapes;
+    let axis_length = shapes.axis_length;
+    let plane_offset = shapes.plane_offset;
+    // Define gizmo meshes, unless they were provided
+    let provided = &shapes.meshes;
+    let arrow_tail_mesh = provided.shaft.clone().unwrap_or_else(|| {
+        meshes.add(Mesh::from(shape::Cylinder {
+            radius: shapes.shaft_radius,
+            height: axis_length,
+            ..Default::default()
+        }))
+    });
+    let tip_mesh = match (&provided.tip, shapes.axis_tip) {
+        (Some(mesh), _) => Some(mesh.clone()),
+        (None, AxisTip::Cone { height, radius }) => Some(meshes.add(Mesh::from(cone::Cone {
+            height,
+            radius,
+            ..Default::default()
+        }))),
+        (None, AxisTip::Cube { size }) => Some(meshes.add(Mesh::from(shape::Box {
+            min_y: 0.0,
+            max_y: size,
+            ..shape::Box::new(size, size, size)
+        }))),
+        (None, AxisTip::None) => None,
+    };
+    let plane_mesh = match &provided.plane {
+        Some(mesh) => Some(mesh.clone()),
+        None if shapes.plane_size > 0.0 => {
+            Some(meshes.add(Mesh::from(shape::Plane::from_size(shapes.plane_size))))
+        }
+        None => None,
+    };
+    let sphere_mesh = provided.center.clone().unwrap_or_else(|| {
+        meshes.add(
+            Mesh::try_from(shape::Icosphere {
+                radius: shapes.center_radius,
+                subdivisions: 3,
+            })
+            .unwrap(),
+        )
+    });
+    let rotation_mesh = provided.ring.clone().unwrap_or_else(|| {
+        meshes.add(Mesh::from(truncated_torus::TruncatedTorus {
+            radius: shapes.ring_radius,
+            ring_radius: shapes.ring_thickness,
+            angle: shapes.ring_angle,
+            ..Default::default()
+        }))
+    });
+    // Thicker copies of the handles, easier to hit with a finger, that don't grow longer so they
+    // don't cover each other.
+    let grow = settings.touch_hit_scale;
+    let shaft_hit_mesh = meshes.add(Mesh::from(shape::Cylinder {
+        radius: shapes.shaft_radius * grow,
         height: axis_length,
         ..Default::default()
     }));
-    let cone_mesh = meshes.add(Mesh::from(cone::Cone {
-        height: 0.25,
-        radius: 0.10,
-        ..Default::default()
-    }));
-    let plane_mesh = meshes.add(Mesh::from(shape::Plane::from_size(plane_size)));
-    let sphere_mesh = meshes.add(
+    let tip_hit_mesh = match shapes.axis_tip {
+        AxisTip::Cone { height, radius } => Some(meshes.add(Mesh::from(cone::Cone {
+            height,
+            radius: radius * grow,
+            ..Default::default()
+        }))),
+        AxisTip::Cube { size } => Some(meshes.add(Mesh::from(shape::Box {
+            min_y: 0.0,
+            max_y: size,
+            ..shape::Box::new(size * grow, size, size * grow)
+        }))),
+        // Provided tips without a built-in shape are hit as drawn.
+        AxisTip::None => tip_mesh.clone(),
+    };
+    let plane_hit_mesh = meshes.add(Mesh::from(shape::Plane::from_size(
+        shapes.plane_size * PLANE_HIT_MARGIN,
+    )));
+    let sphere_hit_mesh = meshes.add(
         Mesh::try_from(shape::Icosphere {
-            radius: 0.25,
+            radius: shapes.center_radius * grow,
             subdivisions: 3,
         })
         .unwrap(),
     );
-    let rotation_mesh = meshes.add(Mesh::from(truncated_torus::TruncatedTorus {
-        radius: arc_radius,
-        ring_radius: 0.04,
+    let rotation_hit_mesh = meshes.add(Mesh::from(truncated_torus::TruncatedTorus {
+        radius: shapes.ring_radius,
+        ring_radius: shapes.ring_thickness * grow,
+        angle: shapes.ring_angle,
         ..Default::default()
     }));
+    let label_meshes = [
+        (AxisLabels::Letters, glyph::GlyphShape::LetterX),
+        (AxisLabels::Letters, glyph::GlyphShape::LetterY),
+        (AxisLabels::Letters, glyph::GlyphShape::LetterZ),
+        (AxisLabels::Shapes, glyph::GlyphShape::Square),
+        (AxisLabels::Shapes, glyph::GlyphShape::Circle),
+        (AxisLabels::Shapes, glyph::GlyphShape::Triangle),
+    ]
+    .map(|(style, shape)| {
+        let mesh = meshes.add(Mesh::from(glyph::Glyph {
+            shape,
+            size: 0.2,
+            ..Default::default()
+        }));
+        (style, mesh)
+    });
     //let cube_mesh = meshes.add(Mesh::from(shape::Cube { size: 0.15 }));
     // Define gizmo materials
-    let s = 0.8;
-    let l = 0.55;
-    let l_selected = 0.7;
-
-    let x = Color::hsl(0.0, s, l);
-    let y = Color::hsl(120.0, s, l);
-    let z = Color::hsl(240.0, s, l);
+    let palette = settings.palette;
+    let plane_alpha = palette.plane_alpha;
 
-    let plane_alpha = 0.5;
+    let x_translation = materials.add(palette.x.into());
+    let x_translation_plane = materials.add(palette.x.with_a(plane_alpha).into());
+    let x_rotation = materials.add(palette.x.into());
 
-    let x_translation = materials.add(x.into());
-    let x_translation_plane = materials.add(x.with_a(plane_alpha).into());
-    let x_rotation = materials.add(x.into());
+    let y_translation = materials.add(palette.y.into());
+    let y_translation_plane = materials.add(palette.y.with_a(plane_alpha).into());
+    let y_rotation = materials.add(palette.y.into());
 
-    let y_translation = materials.add(y.into());
-    let y_translation_plane = materials.add(y.with_a(plane_alpha).into());
-    let y_rotation = materials.add(y.into());
+    let z_translation = materials.add(palette.z.into());
+    let z_translation_plane = materials.add(palette.z.with_a(plane_alpha).into());
+    let z_rotation = materials.add(palette.z.into());
 
-    let z_translation = materials.add(z.into());
-    let z_translation_plane = materials.add(z.with_a(plane_alpha).into());
-    let z_rotation = materials.add(z.into());
+    let v = materials.add(GizmoMaterial::from(palette.view));
+    let sweep = materials.add(GizmoMaterial::from(palette.view.with_a(plane_alpha)));
 
-    let v = materials.add(GizmoMaterial::from(Color::hsl(0., 0.0, l)));
+    let render_layers = RenderLayers::layer(render_layer);
 
     // Build the gizmo using the variables above.
     commands
         .spawn((
             TransformGizmoBundle::default(),
-            On::<Pointer<Move>>::run(
-                move |event: Listener<Pointer<Move>>,
-                      mut assets: ResMut<Assets<GizmoMaterial>>,
-                      handles: Query<&Handle<GizmoMaterial>>| {
-                    let Ok(handle) = handles.get(event.target) else {
-                        return;
-                    };
-                    assets.get_mut(handle).unwrap().color.set_l(l_selected);
-                },
-            ),
-            On::<Pointer<Out>>::run(
-                move |event: Listener<Pointer<Out>>,
-                      mut assets: ResMut<Assets<GizmoMaterial>>,
-                      handles: Query<&Handle<GizmoMaterial>>| {
-                    let Ok(handle) = handles.get(event.target) else {
-                        return;
-                    };
-                    assets.get_mut(handle).unwrap().color.set_l(l);
-                },
-            ),
+            GizmoPickCamera(pick_camera),
+            On::<Pointer<Move>>::run(on_hover),
+            On::<Pointer<Out>>::run(on_hover_end),
             On::<Pointer<DragStart>>::run(on_drag_start),
             On::<Pointer<DragEnd>>::run(on_drag_end),
             On::<Pointer<Drag>>::run(on_drag),
@@ -334,8 +966,9 @@ pub fn build_gizmo(
                     original: Vec3::X,
                     axis: Vec3::X,
                 },
+                TouchHitMesh(shaft_hit_mesh.clone()),
                 NotShadowCaster,
-                RenderLayers::layer(12),
+                render_layers,
                 NoDeselect,
             ));
             parent.spawn((
@@ -353,8 +986,9 @@ pub fn build_gizmo(
                     original: Vec3::Y,
                     axis: Vec3::Y,
                 },
+                TouchHitMesh(shaft_hit_mesh.clone()),
                 NotShadowCaster,
-                RenderLayers::layer(12),
+                render_layers,
                 NoDeselect,
             ));
             parent.spawn((
@@ -372,127 +1006,146 @@ pub fn build_gizmo(
                     original: Vec3::Z,
                     axis: Vec3::Z,
                 },
+                TouchHitMesh(shaft_hit_mesh.clone()),
                 NotShadowCaster,
-                RenderLayers::layer(12),
+                render_layers,
                 NoDeselect,
             ));
 
             // Translation Handles
-            parent.spawn((
-                MaterialMeshBundle {
-                    mesh: cone_mesh.clone(),
-                    material: x_translation.clone(),
-                    transform: Transform::from_matrix(Mat4::from_rotation_translation(
-                        Quat::from_rotation_z(std::f32::consts::PI / -2.0),
-                        Vec3::new(axis_length, 0.0, 0.0),
-                    )),
-                    ..Default::default()
-                },
-                PickableGizmo::default(),
-                TransformGizmoInteraction::TranslateAxis {
-                    original: Vec3::X,
-                    axis: Vec3::X,
-                },
-                NotShadowCaster,
-                RenderLayers::layer(12),
-                NoDeselect,
-            ));
-            parent.spawn((
-                MaterialMeshBundle {
-                    mesh: plane_mesh.clone(),
-                    material: x_translation_plane.clone(),
-                    transform: Transform::from_matrix(Mat4::from_rotation_translation(
-                        Quat::from_rotation_z(std::f32::consts::PI / -2.0),
-                        Vec3::new(0., plane_offset, plane_offset),
-                    )),
-                    ..Default::default()
-                },
-                PickableGizmo::default(),
-                TransformGizmoInteraction::TranslatePlane {
-                    original: Vec3::X,
-                    normal: Vec3::X,
-                },
-                NoBackfaceCulling,
-                NotShadowCaster,
-                RenderLayers::layer(12),
-                NoDeselect,
-            ));
-            parent.spawn((
-                MaterialMeshBundle {
-                    mesh: cone_mesh.clone(),
-                    material: y_translation.clone(),
-                    transform: Transform::from_translation(Vec3::new(0.0, axis_length, 0.0)),
-                    ..Default::default()
-                },
-                PickableGizmo::default(),
-                TransformGizmoInteraction::TranslateAxis {
-                    original: Vec3::Y,
-                    axis: Vec3::Y,
-                },
-                NotShadowCaster,
-                RenderLayers::layer(12),
-                NoDeselect,
-            ));
-            parent.spawn((
-                MaterialMeshBundle {
-                    mesh: plane_mesh.clone(),
-                    material: y_translation_plane.clone(),
-                    transform: Transform::from_translation(Vec3::new(
-                        plane_offset,
-                        0.0,
-                        plane_offset,
-                    )),
-                    ..Default::default()
-                },
-                PickableGizmo::default(),
-                TransformGizmoInteraction::TranslatePlane {
-                    original: Vec3::Y,
-                    normal: Vec3::Y,
-                },
-                NoBackfaceCulling,
-                NotShadowCaster,
-                RenderLayers::layer(12),
-                NoDeselect,
-            ));
-            parent.spawn((
-                MaterialMeshBundle {
-                    mesh: cone_mesh.clone(),
-                    material: z_translation.clone(),
-                    transform: Transform::from_matrix(Mat4::from_rotation_translation(
-                        Quat::from_rotation_x(std::f32::consts::PI / 2.0),
-                        Vec3::new(0.0, 0.0, axis_length),
-                    )),
-                    ..Default::default()
-                },
-                PickableGizmo::default(),
-                TransformGizmoInteraction::TranslateAxis {
-                    original: Vec3::Z,
-                    axis: Vec3::Z,
-                },
-                NotShadowCaster,
-                RenderLayers::layer(12),
-                NoDeselect,
-            ));
-            parent.spawn((
-                MaterialMeshBundle {
-                    mesh: plane_mesh.clone(),
-                    material: z_translation_plane.clone(),
-                    transform: Transform::from_matrix(Mat4::from_rotation_translation(
-                        Quat::from_rotation_x(std::f32::consts::PI / 2.0),
-                        Vec3::new(plane_offset, plane_offset, 0.0),
-                    )),
-                    ..Default::default()
-                },
-                PickableGizmo::default(),
-                TransformGizmoInteraction::TranslatePlane {
-                    original: Vec3::Z,
-                    normal: Vec3::Z,
-                },
-                NoBackfaceCulling,
-                NotShadowCaster,
-                RenderLayers::layer(12),
-                NoDeselect,
-            ));
+            if let (Some(tip_mesh), Some(tip_hit_mesh)) = (&tip_mesh, &tip_hit_mesh) {
+                parent.spawn((
+                    MaterialMeshBundle {
+                        mesh: tip_mesh.clone(),
+                        material: x_translation.clone(),
+                        transform: Transform::from_matrix(Mat4::from_rotation_translation(
+                            Quat::from_rotation_z(std::f32::consts::PI / -2.0),
+                            Vec3::new(axis_length, 0.0, 0.0),
+                        )),
+                        ..Default::default()
+                    },
+                    PickableGizmo::default(),
+                    TransformGizmoInteraction::TranslateAxis {
+                        original: Vec3::X,
+                        axis: Vec3::X,
+                    },
+                    TouchHitMesh(tip_hit_mesh.clone()),
+                    NotShadowCaster,
+                    render_layers,
+                    NoDeselect,
+                ));
+            }
+            if let Some(plane_mesh) = &plane_mesh {
+                parent.spawn((
+                    MaterialMeshBundle {
+                        mesh: plane_mesh.clone(),
+                        material: x_translation_plane.clone(),
+                        transform: Transform::from_matrix(Mat4::from_rotation_translation(
+                            Quat::from_rotation_z(std::f32::consts::PI / -2.0),
+                            Vec3::new(0., plane_offset, plane_offset),
+                        )),
+                        ..Default::default()
+                    },
+                    PickableGizmo::default(),
+                    TransformGizmoInteraction::TranslatePlane {
+                        original: Vec3::X,
+                        normal: Vec3::X,
+                    },
+                    NoBackfaceCulling,
+                    TouchHitMesh(plane_hit_mesh.clone()),
+                    NotShadowCaster,
+                    render_layers,
+                    NoDeselect,
+                ));
+            }
+            if let (Some(tip_mesh), Some(tip_hit_mesh)) = (&tip_mesh, &tip_hit_mesh) {
+                parent.spawn((
+                    MaterialMeshBundle {
+                        mesh: tip_mesh.clone(),
+                        material: y_translation.clone(),
+                        transform: Transform::from_translation(Vec3::new(0.0, axis_length, 0.0)),
+                        ..Default::default()
+                    },
+                    PickableGizmo::default(),
+                    TransformGizmoInteraction::TranslateAxis {
+                        original: Vec3::Y,
+                        axis: Vec3::Y,
+                    },
+                    TouchHitMesh(tip_hit_mesh.clone()),
+                    NotShadowCaster,
+                    render_layers,
+                    NoDeselect,
+                ));
+            }
+            if let Some(plane_mesh) = &plane_mesh {
+                parent.spawn((
+                    MaterialMeshBundle {
+                        mesh: plane_mesh.clone(),
+                        material: y_translation_plane.clone(),
+                        transform: Transform::from_translation(Vec3::new(
+                            plane_offset,
+                            0.0,
+                            plane_offset,
+                        )),
+                        ..Default::default()
+                    },
+                    PickableGizmo::default(),
+                    TransformGizmoInteraction::TranslatePlane {
+                        original: Vec3::Y,
+                        normal: Vec3::Y,
+                    },
+                    NoBackfaceCulling,
+                    TouchHitMesh(plane_hit_mesh.clone()),
+                    NotShadowCaster,
+                    render_layers,
+                    NoDeselect,
+                ));
+            }
+            if let (Some(tip_mesh), Some(tip_hit_mesh)) = (&tip_mesh, &tip_hit_mesh) {
+                parent.spawn((
+                    MaterialMeshBundle {
+                        mesh: tip_mesh.clone(),
+                        material: z_translation.clone(),
+                        transform: Transform::from_matrix(Mat4::from_rotation_translation(
+                            Quat::from_rotation_x(std::f32::consts::PI / 2.0),
+                            Vec3::new(0.0, 0.0, axis_length),
+                        )),
+                        ..Default::default()
+                    },
+                    PickableGizmo::default(),
+                    TransformGizmoInteraction::TranslateAxis {
+                        original: Vec3::Z,
+                        axis: Vec3::Z,
+                    },
+                    TouchHitMesh(tip_hit_mesh.clone()),
+                    NotShadowCaster,
+                    render_layers,
+                    NoDeselect,
+                ));
+            }
+            if let Some(plane_mesh) = &plane_mesh {
+                parent.spawn((
+                    MaterialMeshBundle {
+                        mesh: plane_mesh.clone(),
+                        material: z_translation_plane.clone(),
+                        transform: Transform::from_matrix(Mat4::from_rotation_translation(
+                            Quat::from_rotation_x(std::f32::consts::PI / 2.0),
+                            Vec3::new(plane_offset, plane_offset, 0.0),
+                        )),
+                        ..Default::default()
+                    },
+                    PickableGizmo::default(),
+                    TransformGizmoInteraction::TranslatePlane {
+                        original: Vec3::Z,
+                        normal: Vec3::Z,
+                    },
+                    NoBackfaceCulling,
+                    TouchHitMesh(plane_hit_mesh.clone()),
+                    NotShadowCaster,
+                    render_layers,
+                    NoDeselect,
+                ));
+            }
 
             parent.spawn((
                 MaterialMeshBundle {
@@ -506,11 +1159,36 @@ pub fn build_gizmo(
                     normal: Vec3::Z,
                 },
                 ViewTranslateGizmo,
+                TouchHitMesh(sphere_hit_mesh.clone()),
                 NotShadowCaster,
-                RenderLayers::layer(12),
+                render_layers,
                 NoDeselect,
             ));
 
+            // Axis Labels
+            let label_offset = axis_length + shapes.axis_tip.length() + 0.2;
+            let label_materials = [&x_translation, &y_translation, &z_translation];
+            for (i, (style, mesh)) in label_meshes.iter().enumerate() {
+                let axis = i % 3;
+                parent.spawn((
+                    MaterialMeshBundle {
+                        mesh: mesh.clone(),
+                        material: label_materials[axis].clone(),
+                        transform: Transform::from_translation(Vec3::AXES[axis] * label_offset),
+                        visibility: if *style == settings.axis_labels {
+                            Visibility::Inherited
+                        } else {
+                            Visibility::Hidden
+                        },
+                        ..Default::default()
+                    },
+                    AxisLabel { style: *style },
+                    Pickable::IGNORE,
+                    NotShadowCaster,
+                    render_layers,
+                ));
+            }
+
             // Rotation Arcs
             parent.spawn((
                 MaterialMeshBundle {
@@ -528,8 +1206,9 @@ pub fn build_gizmo(
                     original: Vec3::X,
                     axis: Vec3::X,
                 },
+                TouchHitMesh(rotation_hit_mesh.clone()),
                 NotShadowCaster,
-                RenderLayers::layer(12),
+                render_layers,
                 NoDeselect,
             ));
             parent.spawn((
@@ -544,8 +1223,9 @@ pub fn build_gizmo(
                     original: Vec3::Y,
                     axis: Vec3::Y,
                 },
+                TouchHitMesh(rotation_hit_mesh.clone()),
                 NotShadowCaster,
-                RenderLayers::layer(12),
+                render_layers,
                 NoDeselect,
             ));
             parent.spawn((
@@ -564,10 +1244,26 @@ pub fn build_gizmo(
                     original: Vec3::Z,
                     axis: Vec3::Z,
                 },
+                TouchHitMesh(rotation_hit_mesh.clone()),
                 NotShadowCaster,
-                RenderLayers::layer(12),
+                render_layers,
                 NoDeselect,
             ));
+
+            // Rotation Sweep, rebuilt every frame while rotating
+            parent.spawn((
+                MaterialMeshBundle {
+                    mesh: meshes.add(Mesh::new(PrimitiveTopology::TriangleList)),
+                    material: sweep,
+                    visibility: Visibility::Hidden,
+                    ..Default::default()
+                },
+                RotationSweep,
+                Pickable::IGNORE,
+                NoFrustumCulling,
+                NotShadowCaster,
+                render_layers,
+            ));
         });
 
     commands.spawn((
@@ -580,6 +1276,86 @@ pub fn build_gizmo(
             ..Default::default()
         },
         InternalGizmoCamera,
-        RenderLayers::layer(12),
+        GizmoPickCamera(pick_camera),
+        // The overlay shares the main camera's target, so UI would otherwise be drawn twice.
+        #[cfg(feature = "delta_readout")]
+        bevy::ui::camera_config::UiCameraConfig { show_ui: false },
+        render_layers,
     ));
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::camera::NormalizedRenderTarget;
+    use bevy_mod_picking::{
+        backend::HitData,
+        pointer::{Location, PointerButton, PointerId},
+        prelude::EventListenerPlugin,
+    };
+
+    use super::*;
+
+    fn pointer_event<E: std::fmt::Debug + Clone + Reflect>(target: Entity, event: E) -> Pointer<E> {
+        let location = Location {
+            target: NormalizedRenderTarget::Image(Handle::default()),
+            position: Vec2::ZERO,
+        };
+        Pointer::new(PointerId::Mouse, location, target, event)
+    }
+
+    #[test]
+    fn drags_handles_nested_below_the_gizmo() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            EventListenerPlugin::<Pointer<DragStart>>::default(),
+            EventListenerPlugin::<Pointer<DragEnd>>::default(),
+        ));
+        let interaction = TransformGizmoInteraction::TranslateAxis {
+            original: Vec3::X,
+            axis: Vec3::X,
+        };
+        let gizmo = app
+            .world
+            .spawn((
+                TransformGizmoBundle::default(),
+                On::<Pointer<DragStart>>::run(on_drag_start),
+                On::<Pointer<DragEnd>>::run(on_drag_end),
+            ))
+            .id();
+        let decoration = app.world.spawn(SpatialBundle::default()).id();
+        let handle = app
+            .world
+            .spawn((SpatialBundle::default(), interaction))
+            .id();
+        app.world.entity_mut(gizmo).add_child(decoration);
+        app.world.entity_mut(decoration).add_child(handle);
+
+        let hit = HitData::new(Entity::PLACEHOLDER, 0.0, None, None);
+        for target in [decoration, handle] {
+            app.world.send_event(pointer_event(
+                target,
+                DragStart {
+                    button: PointerButton::Primary,
+                    hit: hit.clone(),
+                },
+            ));
+            app.update();
+            let started = app.world.get::<TransformGizmo>(gizmo).unwrap();
+            // Decorations aren't handles, and don't start a drag.
+            let expected = (target == handle).then_some(interaction);
+            assert_eq!(started.current_interaction(), expected);
+
+            app.world.send_event(pointer_event(
+                target,
+                DragEnd {
+                    button: PointerButton::Primary,
+                    distance: Vec2::ZERO,
+                },
+            ));
+            app.update();
+            let ended = app.world.get::<TransformGizmo>(gizmo).unwrap();
+            assert_eq!(ended.current_interaction(), None);
+        }
+    }
+}
@@ -1,6 +1,14 @@
-use bevy::{prelude::*, render::camera::Camera, transform::TransformSystem};
+use bevy::{
+    prelude::*,
+    render::camera::{Camera, NormalizedRenderTarget},
+    transform::TransformSystem,
+    window::PrimaryWindow,
+};
 
-use crate::{GizmoPickSource, TransformGizmoSettings, TransformGizmoSystem};
+use crate::{
+    picking::displayed_target, GizmoPickCamera, GizmoPickSource, GizmoTargetRect,
+    TransformGizmoSettings, TransformGizmoSystem,
+};
 
 pub struct Ui3dNormalization;
 impl Plugin for Ui3dNormalization {
@@ -11,69 +19,179 @@ impl Plugin for Ui3dNormalization {
                 .in_set(TransformGizmoSystem::NormalizeSet)
                 .after(TransformSystem::TransformPropagate)
                 .after(TransformGizmoSystem::Place)
-                .run_if(|settings: Res<TransformGizmoSettings>| settings.enabled),
+                .run_if(|settings: Option<Res<TransformGizmoSettings>>| {
+                    settings.map_or(true, |settings| settings.enabled)
+                }),
         );
     }
 }
 
 /// Marker struct that marks entities with meshes that should be scaled relative to the camera.
+///
+/// Works for any entity, such as markers, icons and labels, to keep them the same size on screen.
+/// Entities with a parent get a local scale that gives them the desired size in world space.
 #[derive(Component, Debug)]
 pub struct Normalize3d {
     /// Length of the object in world space units
     pub size_in_world: f32,
     /// Desired length of the object in pixels
     pub desired_pixel_size: f32,
+    /// Smallest world space scale the object is given, however close the camera gets.
+    pub min_scale: Option<f32>,
+    /// Largest world space scale the object is given, however far the camera gets.
+    pub max_scale: Option<f32>,
+    /// Multiply `desired_pixel_size` by the scale factor of the window, so the object keeps the
+    /// same physical size on HiDPI monitors. Otherwise it is a size in physical pixels.
+    pub scale_with_dpi: bool,
+    /// The camera the object keeps its size in. Defaults to the picking camera of the gizmo, or
+    /// the only [`GizmoPickSource`] camera. Required for other entities when there are several
+    /// [`GizmoPickSource`] cameras.
+    pub camera: Option<Entity>,
 }
 impl Normalize3d {
     pub fn new(size_in_world: f32, desired_pixel_size: f32) -> Self {
         Normalize3d {
             size_in_world,
             desired_pixel_size,
+            min_scale: None,
+            max_scale: None,
+            scale_with_dpi: true,
+            camera: None,
         }
     }
+
+    /// Keeps the world space scale of the object between `min` and `max`.
+    pub fn with_scale_limits(mut self, min: Option<f32>, max: Option<f32>) -> Self {
+        self.min_scale = min;
+        self.max_scale = max;
+        self
+    }
+
+    pub fn with_dpi_scaling(mut self, scale_with_dpi: bool) -> Self {
+        self.scale_with_dpi = scale_with_dpi;
+        self
+    }
+
+    /// Keeps the size of the object on screen in `camera`, which can be any camera.
+    pub fn with_camera(mut self, camera: Entity) -> Self {
+        self.camera = Some(camera);
+        self
+    }
 }
 
 #[allow(clippy::type_complexity)]
 pub fn normalize(
     mut query: ParamSet<(
-        Query<(&GlobalTransform, &Camera), With<GizmoPickSource>>,
-        Query<(&mut Transform, &mut GlobalTransform, &Normalize3d)>,
+        Query<(
+            Entity,
+            &GlobalTransform,
+            &Camera,
+            Option<&GizmoTargetRect>,
+            Has<GizmoPickSource>,
+        )>,
+        Query<(
+            &mut Transform,
+            &mut GlobalTransform,
+            &Normalize3d,
+            Option<&GizmoPickCamera>,
+        )>,
     )>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    windows: Query<&Window>,
+    mut logged_camera_error: Local<bool>,
 ) {
-    // TODO: can be improved by manually specifying the active camera to normalize against. The
-    // majority of cases will only use a single camera for this viewer, so this is sufficient.
-    let (camera_position, camera) = if let Ok((camera_position, camera)) = query.p0().get_single() {
-        (camera_position.to_owned(), camera.to_owned())
-    } else {
-        error!("Not exactly one picking camera.");
-        return;
-    };
-    let view = camera_position.compute_matrix().inverse();
+    let primary_window = primary_window.get_single().ok();
+    let cameras: Vec<_> = query
+        .p0()
+        .iter()
+        .map(
+            |(entity, camera_position, camera, target_rect, is_pick_source)| {
+                // Sizes are given in pixels of the window, which differ from pixels of an image target
+                // shown scaled inside it.
+                let pixel_ratio = target_rect
+                    .and_then(|target_rect| target_rect.target_pixels_per_window_pixel(camera))
+                    .unwrap_or(1.0);
+                let window = match displayed_target(camera, target_rect, primary_window) {
+                    Some(NormalizedRenderTarget::Window(window)) => Some(window.entity()),
+                    _ => None,
+                };
+                let scale_factor = window
+                    .and_then(|window| windows.get(window).ok())
+                    .map_or(1.0, |window| window.scale_factor() as f32);
+                let view = camera_position.compute_matrix().inverse();
+                (
+                    entity,
+                    is_pick_source,
+                    view,
+                    camera.to_owned(),
+                    pixel_ratio,
+                    scale_factor,
+                )
+            },
+        )
+        .collect();
+    let pick_sources = cameras
+        .iter()
+        .filter(|(_, is_pick_source, ..)| *is_pick_source);
 
-    for (mut transform, mut global_transform, normalize) in &mut query.p1() {
+    for (mut transform, mut global_transform, normalize, pick_camera) in &mut query.p1() {
+        // Use the chosen camera, or the gizmo's own picking camera. Other entities use the only
+        // picking camera.
+        let camera = match normalize
+            .camera
+            .or(pick_camera.map(|pick_camera| pick_camera.0))
+        {
+            Some(camera) => cameras.iter().find(|(entity, ..)| *entity == camera),
+            None if pick_sources.clone().count() == 1 => pick_sources.clone().next(),
+            None => {
+                if !*logged_camera_error {
+                    error!("Not exactly one picking camera, set `Normalize3d::camera`.");
+                    *logged_camera_error = true;
+                }
+                None
+            }
+        };
+        let Some((_, _, view, camera, pixel_ratio, scale_factor)) = camera else {
+            continue;
+        };
         let distance = view.transform_point3(global_transform.translation()).z;
         let gt = global_transform.compute_transform();
-        let pixel_end = if let Some(coords) = Camera::world_to_viewport(
-            &camera,
-            &GlobalTransform::default(),
-            Vec3::new(normalize.size_in_world * gt.scale.x, 0.0, distance),
-        ) {
-            coords
-        } else {
+        let Some(viewport_size) = camera.logical_viewport_size() else {
             continue;
         };
-        let pixel_root = if let Some(coords) = Camera::world_to_viewport(
-            &camera,
-            &GlobalTransform::default(),
-            Vec3::new(0.0, 0.0, distance),
-        ) {
-            coords
-        } else {
+        // Project a segment of the object's size facing the camera. Clip space `w` is the view
+        // depth for perspective projections, and 1 for orthographic ones, which keep the same
+        // size at any distance.
+        let projection = camera.projection_matrix();
+        let root = projection * Vec4::new(0.0, 0.0, distance, 1.0);
+        let end = projection * Vec4::new(normalize.size_in_world * gt.scale.x, 0.0, distance, 1.0);
+        if root.w <= 0.0 {
+            continue; // Behind a perspective camera.
+        }
+        let actual_pixel_size = (end.x - root.x).abs() / root.w * viewport_size.x / 2.0;
+        if actual_pixel_size <= 0.0 {
             continue;
+        }
+        // Sizes on screen are measured in logical pixels, which already follow the scale factor.
+        let desired_pixel_size = if normalize.scale_with_dpi {
+            normalize.desired_pixel_size
+        } else {
+            normalize.desired_pixel_size / scale_factor
         };
-        let actual_pixel_size = pixel_root.distance(pixel_end);
-        let required_scale = normalize.desired_pixel_size / actual_pixel_size;
-        transform.scale = gt.scale * Vec3::splat(required_scale);
-        *global_transform = (*transform).into();
+        let mut required_scale = desired_pixel_size * pixel_ratio / actual_pixel_size;
+        let world_scale = gt.scale.x * required_scale;
+        let clamped_scale = world_scale
+            .max(normalize.min_scale.unwrap_or(f32::MIN))
+            .min(normalize.max_scale.unwrap_or(f32::MAX));
+        if clamped_scale != world_scale && world_scale > 0.0 {
+            required_scale *= clamped_scale / world_scale;
+        }
+        // Scaling the local transform scales the global one just as much, parent or not.
+        transform.scale *= required_scale;
+        *global_transform = Transform {
+            scale: gt.scale * required_scale,
+            ..gt
+        }
+        .into();
     }
 }
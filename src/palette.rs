@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+
+use crate::TransformGizmoInteraction;
+
+/// Colors used to draw the gizmo handles.
+///
+/// Besides the default red/green/blue palette, presets are provided that stay distinguishable
+/// for the common forms of color blindness. Combine them with [`AxisLabels`] to tell the axes
+/// apart without relying on color at all.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GizmoPalette {
+    pub x: Color,
+    pub y: Color,
+    pub z: Color,
+    /// Color of the handle in the center of the gizmo, which translates in the view plane.
+    pub view: Color,
+    /// Alpha of the plane translation handles.
+    pub plane_alpha: f32,
+    /// Amount added to the HSL lightness of a handle while it is highlighted.
+    pub highlight_lightness: f32,
+}
+
+impl Default for GizmoPalette {
+    fn default() -> Self {
+        Self {
+            x: Color::hsl(0.0, 0.8, 0.55),
+            y: Color::hsl(120.0, 0.8, 0.55),
+            z: Color::hsl(240.0, 0.8, 0.55),
+            view: Color::hsl(0.0, 0.0, 0.55),
+            plane_alpha: 0.5,
+            highlight_lightness: 0.15,
+        }
+    }
+}
+
+impl GizmoPalette {
+    /// Vermillion, yellow and blue, which differ along the blue-yellow axis and in lightness.
+    pub fn deuteranopia() -> Self {
+        Self {
+            x: Color::hex("D55E00").unwrap(),
+            y: Color::hex("F0E442").unwrap(),
+            z: Color::hex("0072B2").unwrap(),
+            ..default()
+        }
+    }
+
+    /// Like [`GizmoPalette::deuteranopia`], but with a brighter orange for the X axis as reds
+    /// appear darker with protanopia.
+    pub fn protanopia() -> Self {
+        Self {
+            x: Color::hex("E69F00").unwrap(),
+            y: Color::hex("F0E442").unwrap(),
+            z: Color::hex("0072B2").unwrap(),
+            ..default()
+        }
+    }
+
+    /// Red, light teal and dark blue, which differ along the red-green axis and in lightness.
+    pub fn tritanopia() -> Self {
+        Self {
+            x: Color::hex("DC3220").unwrap(),
+            y: Color::hex("4FD1C5").unwrap(),
+            z: Color::hex("1F4E9A").unwrap(),
+            ..default()
+        }
+    }
+
+    /// Color of the handles belonging to the axis `axis`, given in the gizmo's local space.
+    pub fn axis_color(&self, axis: Vec3) -> Color {
+        if axis == Vec3::X {
+            self.x
+        } else if axis == Vec3::Y {
+            self.y
+        } else if axis == Vec3::Z {
+            self.z
+        } else {
+            self.view
+        }
+    }
+
+    /// Color of a handle with the given interaction when it is not highlighted.
+    pub fn handle_color(&self, interaction: &TransformGizmoInteraction) -> Color {
+        match *interaction {
+            TransformGizmoInteraction::TranslatePlane { original, .. }
+                if original != Vec3::ZERO =>
+            {
+                self.axis_color(original).with_a(self.plane_alpha)
+            }
+            TransformGizmoInteraction::TranslateAxis { original, .. }
+            | TransformGizmoInteraction::TranslatePlane { original, .. }
+            | TransformGizmoInteraction::RotateAxis { original, .. }
+            | TransformGizmoInteraction::ScaleAxis { original, .. } => self.axis_color(original),
+            TransformGizmoInteraction::Custom => self.view,
+        }
+    }
+
+    /// Returns the highlighted version of `color`.
+    pub fn highlight(&self, mut color: Color) -> Color {
+        let l = color.l();
+        color.set_l((l + self.highlight_lightness).min(1.0));
+        color
+    }
+}
+
+/// Marks the axes of the gizmo with a glyph next to the tip of each translation arrow.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AxisLabels {
+    #[default]
+    None,
+    /// The letters X, Y and Z.
+    Letters,
+    /// A square for X, a circle for Y and a triangle for Z.
+    Shapes,
+}
@@ -1,13 +1,22 @@
-use bevy::prelude::*;
-use bevy_mod_raycast::prelude::{RaycastMesh, RaycastMethod, RaycastSource, RaycastSystem};
+use bevy::{
+    prelude::*,
+    render::camera::NormalizedRenderTarget,
+    utils::EntityHashMap,
+    window::{PrimaryWindow, WindowRef},
+};
+use bevy_mod_picking::{
+    picking_core::PickSet,
+    pointer::{Location, PointerId, PointerLocation},
+};
+use bevy_mod_raycast::prelude::{Ray3d, RaycastMesh, RaycastMethod, RaycastSource, RaycastSystem};
 
-use crate::{TransformGizmoSettings, TransformGizmoSystem};
+use crate::{GizmoPickCamera, TransformGizmo, TransformGizmoSettings, TransformGizmoSystem};
 
 pub type GizmoPickSource = RaycastSource<GizmoRaycastSet>;
 pub type PickableGizmo = RaycastMesh<GizmoRaycastSet>;
 
 /// Plugin with all the systems and resources used to raycast against gizmo handles separately from
-/// the `bevy_mod_picking` plugin.
+/// the `bevy_mod_picking` plugin, or to follow its pointers in [`GizmoPickingMode::Pointers`].
 pub struct GizmoPickingPlugin;
 
 impl Plugin for GizmoPickingPlugin {
@@ -15,14 +24,35 @@ impl Plugin for GizmoPickingPlugin {
         app.add_systems(
             PreUpdate,
             (
-                update_gizmo_raycast_with_cursor,
-                bevy_mod_raycast::prelude::build_rays::<GizmoRaycastSet>
+                (
+                    update_gizmo_raycast_with_cursor.run_if(in_mode(GizmoPickingMode::Raycast)),
+                    update_gizmo_raycast_with_pointers.run_if(in_mode(GizmoPickingMode::Pointers)),
+                ),
+                crate::gamepad::move_virtual_cursor
+                    .run_if(|settings: Res<TransformGizmoSettings>| settings.gamepad.is_some()),
+                (
+                    bevy_mod_raycast::prelude::build_rays::<GizmoRaycastSet>,
+                    apply_world_rays,
+                    build_screenspace_rays,
+                )
+                    .chain()
                     .in_set(RaycastSystem::BuildRays::<GizmoRaycastSet>),
                 bevy_mod_raycast::prelude::update_raycast::<GizmoRaycastSet>
-                    .in_set(RaycastSystem::UpdateRaycast::<GizmoRaycastSet>),
+                    .in_set(RaycastSystem::UpdateRaycast::<GizmoRaycastSet>)
+                    .run_if(
+                        |settings: Res<TransformGizmoSettings>,
+                         world_rays: Query<(), With<GizmoRay>>| {
+                            settings.picking_mode != GizmoPickingMode::Pointers
+                                || settings.gamepad.is_some()
+                                || !world_rays.is_empty()
+                        },
+                    ),
             )
                 .chain()
                 .in_set(TransformGizmoSystem::RaycastSet)
+                // Pointers are up to date, and the ray is ready before drag events are handled.
+                .after(PickSet::ProcessInput)
+                .before(PickSet::Backend)
                 .run_if(|settings: Res<TransformGizmoSettings>| settings.enabled),
         );
     }
@@ -31,15 +61,238 @@ impl Plugin for GizmoPickingPlugin {
 #[derive(Reflect, Clone)]
 pub struct GizmoRaycastSet;
 
-/// Update the gizmo's raycasting source with the current mouse position.
+/// Where the picking ray used to drag the gizmo comes from. Hovering and grabbing the handles is
+/// always decided by `bevy_mod_picking`, where the gizmo's overlay camera takes priority over the
+/// scene.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GizmoPickingMode {
+    /// Raycast from the mouse cursor of the window the picking camera renders to, or from the
+    /// finger touching the primary window.
+    #[default]
+    Raycast,
+    /// Follow the `bevy_mod_picking` pointer dragging the gizmo, or any pointer over the picking
+    /// camera's render target while idle. Touch, pen and custom pointers can drag the gizmo, and
+    /// no separate raycast is done against the handles.
+    Pointers,
+    /// Leave the ray of the [`GizmoPickSource`] alone, so it can be set from any source, and
+    /// drive the gizmo with [`GizmoControlEvent`](crate::GizmoControlEvent)s. Set its
+    /// `cast_method` to a position in logical pixels of the camera's render target, or insert a
+    /// [`GizmoRay`] next to it. The handles hit by the ray are found in its intersections.
+    Manual,
+}
+
+fn in_mode(mode: GizmoPickingMode) -> impl Fn(Res<TransformGizmoSettings>) -> bool {
+    move |settings| settings.picking_mode == mode
+}
+
+/// A world-space ray to pick and drag the gizmo with in place of the cursor, such as the ray of a
+/// tracked controller. Insert it next to the [`GizmoPickSource`] of a camera, and set it to `None`
+/// to go back to the cursor.
+///
+/// `bevy_mod_picking` only knows about screen-space pointers, so find the handle hit by the ray in
+/// the intersections of the [`GizmoPickSource`], and grab it with a
+/// [`GizmoControlEvent`](crate::GizmoControlEvent).
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct GizmoRay(pub Option<Ray>);
+
+/// Where the render target of a picking camera is shown inside a window, for cameras rendering
+/// to an [`Image`] that is displayed in a UI panel. Cursor positions in the window are remapped
+/// into the image before picking.
+///
+/// `bevy_mod_picking` doesn't know the image is shown in the window, so pointers in the window
+/// pick the handles of such cameras by their distance on screen, within
+/// [`TransformGizmoSettings::handle_pick_tolerance`] or [`DEFAULT_TARGET_RECT_TOLERANCE`] logical
+/// pixels. Pointers placed on the image itself pick them as usual.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct GizmoTargetRect {
+    /// The window the image is shown in.
+    pub window: Entity,
+    /// Area covered by the image, in logical pixels of the window.
+    pub rect: Rect,
+}
+
+impl GizmoTargetRect {
+    /// Converts a position in the window to logical pixels of the camera's render target.
+    pub fn to_target(&self, camera: &Camera, position: Vec2) -> Option<Vec2> {
+        let target_size = camera.logical_target_size()?;
+        Some((position - self.rect.min) / self.rect.size() * target_size)
+    }
+
+    /// Converts a position in logical pixels of the camera's render target to the window.
+    pub fn to_window(&self, camera: &Camera, position: Vec2) -> Option<Vec2> {
+        let target_size = camera.logical_target_size()?;
+        Some(self.rect.min + position / target_size * self.rect.size())
+    }
+
+    /// How many logical pixels of the render target are covered by one pixel of the window.
+    pub fn target_pixels_per_window_pixel(&self, camera: &Camera) -> Option<f32> {
+        Some(camera.logical_target_size()?.x / self.rect.width())
+    }
+}
+
+/// Pick tolerance, in logical pixels, for pointers in the window showing a camera with a
+/// [`GizmoTargetRect`], when [`TransformGizmoSettings::handle_pick_tolerance`] is unset.
+pub const DEFAULT_TARGET_RECT_TOLERANCE: f32 = 8.0;
+
+/// Position of a pointer over a picking camera, in logical pixels of the camera's render target.
+/// Pointers are over the camera when they are on its own render target, or on the window showing
+/// its image if it has a [`GizmoTargetRect`].
+pub(crate) fn pointer_position(
+    camera: &Camera,
+    target_rect: Option<&GizmoTargetRect>,
+    primary_window: Option<Entity>,
+    location: &Location,
+) -> Option<Vec2> {
+    if Some(&location.target) == camera.target.normalize(primary_window).as_ref() {
+        return Some(location.position);
+    }
+    let target_rect = target_rect?;
+    let window = WindowRef::Entity(target_rect.window).normalize(None)?;
+    (location.target == NormalizedRenderTarget::Window(window))
+        .then(|| target_rect.to_target(camera, location.position))
+        .flatten()
+}
+
+/// Converts a position in logical pixels of the camera's render target to its viewport.
+pub(crate) fn target_to_viewport(camera: &Camera, position: Vec2) -> Option<Vec2> {
+    Some(position - camera.logical_viewport_rect()?.min)
+}
+
+/// Update the gizmo's raycasting source with the current mouse position, in logical pixels of the
+/// picking camera's render target.
 fn update_gizmo_raycast_with_cursor(
     mut cursor: EventReader<CursorMoved>,
-    mut query: Query<&mut GizmoPickSource>,
+    touches: Res<Touches>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    gizmos: Query<(&TransformGizmo, &GizmoPickCamera)>,
+    mut query: Query<(
+        Entity,
+        &mut GizmoPickSource,
+        &Camera,
+        Option<&GizmoTargetRect>,
+    )>,
 ) {
-    for mut pick_source in &mut query {
-        // Grab the most recent cursor event if it exists:
-        if let Some(cursor_latest) = cursor.read().last() {
-            pick_source.cast_method = RaycastMethod::Screenspace(cursor_latest.position);
+    let events: Vec<&CursorMoved> = cursor.read().collect();
+    let primary_window = primary_window.get_single().ok();
+    for (entity, mut pick_source, camera, target_rect) in &mut query {
+        let Some(NormalizedRenderTarget::Window(window)) =
+            displayed_target(camera, target_rect, primary_window)
+        else {
+            continue;
+        };
+        let window = window.entity();
+        // Touches are only reported for the primary window. Follow the finger dragging the gizmo,
+        // or a finger that just touched down, as it may be about to grab a handle.
+        let dragging = gizmos
+            .iter()
+            .find(|(_, pick_camera)| pick_camera.0 == entity)
+            .and_then(|(gizmo, _)| gizmo.pointer);
+        let touch = match dragging {
+            _ if Some(window) != primary_window => None,
+            Some(PointerId::Touch(id)) => touches.get_pressed(id),
+            Some(_) => None,
+            None => touches.iter_just_pressed().last(),
+        };
+        // Otherwise grab the most recent cursor event in that window, if it exists:
+        let Some(window_position) = touch.map(|touch| touch.position()).or_else(|| {
+            events
+                .iter()
+                .rev()
+                .find(|event| event.window == window)
+                .map(|event| event.position)
+        }) else {
+            continue;
+        };
+        let position = match target_rect {
+            Some(target_rect) => target_rect.to_target(camera, window_position),
+            None => Some(window_position),
+        };
+        if let Some(position) = position {
+            pick_source.cast_method = RaycastMethod::Screenspace(position);
         }
     }
 }
+
+/// The render target a picking camera is shown on: the window showing its image if it has a
+/// [`GizmoTargetRect`], or its own render target.
+pub(crate) fn displayed_target(
+    camera: &Camera,
+    target_rect: Option<&GizmoTargetRect>,
+    primary_window: Option<Entity>,
+) -> Option<NormalizedRenderTarget> {
+    match target_rect {
+        Some(target_rect) => WindowRef::Entity(target_rect.window)
+            .normalize(None)
+            .map(NormalizedRenderTarget::Window),
+        None => camera.target.normalize(primary_window),
+    }
+}
+
+/// Update the gizmo's raycasting source with the location of a `bevy_mod_picking` pointer, in
+/// logical pixels of the picking camera's render target.
+fn update_gizmo_raycast_with_pointers(
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    pointers: Query<(&PointerId, &PointerLocation)>,
+    gizmos: Query<(&TransformGizmo, &GizmoPickCamera)>,
+    mut query: Query<(
+        Entity,
+        &mut GizmoPickSource,
+        &Camera,
+        Option<&GizmoTargetRect>,
+    )>,
+) {
+    let primary_window = primary_window.get_single().ok();
+    for (entity, mut pick_source, camera, target_rect) in &mut query {
+        // While dragging, only the pointer that grabbed the gizmo moves it.
+        let dragging = gizmos
+            .iter()
+            .find(|(_, pick_camera)| pick_camera.0 == entity)
+            .and_then(|(gizmo, _)| gizmo.pointer);
+        let Some(position) = pointers
+            .iter()
+            .filter(|(id, _)| dragging.map_or(true, |dragging| **id == dragging))
+            .filter_map(|(_, location)| location.location())
+            .find_map(|location| pointer_position(camera, target_rect, primary_window, location))
+        else {
+            continue;
+        };
+        pick_source.cast_method = RaycastMethod::Screenspace(position);
+    }
+}
+
+/// Replaces the rays of the picking cameras with a [`GizmoRay`] set, and goes back to the last
+/// screenspace position once it is unset.
+fn apply_world_rays(
+    mut query: Query<(Entity, &mut GizmoPickSource, Option<&GizmoRay>)>,
+    mut screen_positions: Local<EntityHashMap<Entity, Vec2>>,
+) {
+    for (entity, mut pick_source, world_ray) in &mut query {
+        let Some(&GizmoRay(Some(ray))) = world_ray else {
+            if let Some(position) = screen_positions.remove(&entity) {
+                if matches!(pick_source.cast_method, RaycastMethod::Transform) {
+                    pick_source.cast_method = RaycastMethod::Screenspace(position);
+                }
+            }
+            continue;
+        };
+        if let RaycastMethod::Screenspace(position) = pick_source.cast_method {
+            screen_positions.insert(entity, position);
+        }
+        // Not a screenspace ray, so drags don't fall back to following a cursor.
+        pick_source.cast_method = RaycastMethod::Transform;
+        pick_source.ray = Some(Ray3d::from(ray));
+    }
+}
+
+/// Rebuilds screenspace rays relative to the camera's own render target and viewport. The rays
+/// built by `bevy_mod_raycast` assume the camera renders to the primary window.
+fn build_screenspace_rays(mut query: Query<(&mut GizmoPickSource, &Camera, &GlobalTransform)>) {
+    for (mut pick_source, camera, camera_transform) in &mut query {
+        let RaycastMethod::Screenspace(position) = pick_source.cast_method else {
+            continue;
+        };
+        pick_source.ray = target_to_viewport(camera, position)
+            .and_then(|position| camera.viewport_to_world(camera_transform, position))
+            .map(Ray3d::from);
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use bevy_mod_raycast::prelude::RaycastMethod;
+
+use crate::{
+    GizmoDragDelta, GizmoPickCamera, GizmoPickSource, GizmoTargetRect, TransformGizmo,
+    TransformGizmoInteraction, TransformGizmoSettings, TransformGizmoSystem,
+};
+
+/// Offset of the readout from the cursor, in logical pixels.
+const CURSOR_OFFSET: Vec2 = Vec2::new(16.0, 16.0);
+
+/// Plugin showing the change applied by the current drag as a label next to the cursor.
+pub struct DeltaReadoutPlugin;
+
+impl Plugin for DeltaReadoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_delta_readout).add_systems(
+            PostUpdate,
+            update_delta_readout
+                .in_set(TransformGizmoSystem::MainSet)
+                .run_if(|settings: Res<TransformGizmoSettings>| settings.enabled),
+        );
+    }
+}
+
+/// Marks the text node used to display the drag delta.
+#[derive(Component)]
+struct DeltaReadout;
+
+fn spawn_delta_readout(mut commands: Commands) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: 16.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        },
+        DeltaReadout,
+    ));
+}
+
+fn update_delta_readout(
+    settings: Res<TransformGizmoSettings>,
+    gizmo: Query<(&TransformGizmo, &GizmoPickCamera)>,
+    pick_source: Query<(&GizmoPickSource, &Camera, Option<&GizmoTargetRect>)>,
+    mut readout: Query<(&mut Text, &mut Style, &mut Visibility), With<DeltaReadout>>,
+) {
+    let Ok((mut text, mut style, mut visibility)) = readout.get_single_mut() else {
+        return;
+    };
+    let drag = gizmo.iter().find_map(|(gizmo, pick_camera)| {
+        Some((
+            gizmo.current_interaction()?,
+            gizmo.drag_delta()?,
+            pick_camera.0,
+        ))
+    });
+    let cursor = match drag.and_then(|(.., pick_camera)| pick_source.get(pick_camera).ok()) {
+        Some((source, camera, target_rect)) => match (&source.cast_method, target_rect) {
+            (RaycastMethod::Screenspace(cursor), Some(target_rect)) => {
+                target_rect.to_window(camera, *cursor)
+            }
+            (RaycastMethod::Screenspace(cursor), None) => Some(*cursor),
+            _ => None,
+        },
+        None => None,
+    };
+    let (Some((interaction, delta, _)), Some(cursor), true) =
+        (drag, cursor, settings.show_delta_readout)
+    else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    text.sections[0].value = format_delta(interaction, delta);
+    style.left = Val::Px(cursor.x + CURSOR_OFFSET.x);
+    style.top = Val::Px(cursor.y + CURSOR_OFFSET.y);
+    visibility.set_if_neq(Visibility::Inherited);
+}
+
+/// Formats axis translations as a signed distance along the axis, plane translations as the
+/// distance moved, and rotations in degrees.
+fn format_delta(interaction: TransformGizmoInteraction, delta: GizmoDragDelta) -> String {
+    match (interaction, delta) {
+        (
+            TransformGizmoInteraction::TranslateAxis { axis, .. },
+            GizmoDragDelta::Translation(translation),
+        ) => format!("{:+.2} m", translation.dot(axis.normalize())),
+        (_, GizmoDragDelta::Translation(translation)) => {
+            format!("{:.2} m", translation.length())
+        }
+        (_, GizmoDragDelta::Rotation(angle)) => format!("{:+.1}°", angle.to_degrees()),
+    }
+}
@@ -0,0 +1,153 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::prelude::*;
+
+/// Shapes and sizes of the gizmo handles, read when a gizmo is built. Start from one of the
+/// presets, tweak the sizes, or swap in meshes of your own with [`GizmoHandleMeshes`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GizmoShapes {
+    /// Length of the translation axes, from the center to the base of the tip.
+    pub axis_length: f32,
+    /// Radius of the translation axis shafts.
+    pub shaft_radius: f32,
+    /// Shape at the end of each translation axis.
+    pub axis_tip: AxisTip,
+    /// Side of the square plane handles. Set to `0.0` to leave them out.
+    pub plane_size: f32,
+    /// Distance from the center to the center of the plane handles, along both of their axes.
+    pub plane_offset: f32,
+    /// Radius of the view translation handle at the center.
+    pub center_radius: f32,
+    /// Radius of the rotation rings, and of the sector showing the angle of a rotation drag.
+    pub ring_radius: f32,
+    /// Radius of the tube the rotation rings are made of.
+    pub ring_thickness: f32,
+    /// Angle covered by each rotation ring, in radians. `TAU` gives full circles.
+    pub ring_angle: f32,
+    /// Meshes replacing the built-in ones, such as meshes loaded from a glTF file.
+    pub meshes: GizmoHandleMeshes,
+}
+
+/// Shape at the end of a translation axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AxisTip {
+    Cone { height: f32, radius: f32 },
+    Cube { size: f32 },
+    None,
+}
+
+impl AxisTip {
+    /// How far the tip reaches past the end of the axis.
+    pub fn length(&self) -> f32 {
+        match *self {
+            AxisTip::Cone { height, .. } => height,
+            AxisTip::Cube { size } => size,
+            AxisTip::None => 0.0,
+        }
+    }
+}
+
+/// Meshes used for the gizmo handles in place of the built-in ones, placed and colored the same
+/// way. Each mesh is modeled for the Y axis, at the size it should be drawn:
+///
+/// - `shaft`: along Y, centered on the origin and `axis_length` long.
+/// - `tip`: pointing up Y, with its base on the origin.
+/// - `plane`: in the XZ plane, centered on the origin.
+/// - `center`: centered on the origin.
+/// - `ring`: around Y in the XZ plane, centered on the origin, starting from +X towards +Z.
+///
+/// Meshes from a glTF file can be loaded with labels such as `"gizmo.glb#Mesh0/Primitive0"`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GizmoHandleMeshes {
+    pub shaft: Option<Handle<Mesh>>,
+    pub tip: Option<Handle<Mesh>>,
+    pub plane: Option<Handle<Mesh>>,
+    pub center: Option<Handle<Mesh>>,
+    pub ring: Option<Handle<Mesh>>,
+}
+
+impl Default for GizmoShapes {
+    fn default() -> Self {
+        let axis_length = 1.3;
+        let plane_size = axis_length * 0.35;
+        Self {
+            axis_length,
+            shaft_radius: 0.04,
+            axis_tip: AxisTip::Cone {
+                height: 0.25,
+                radius: 0.10,
+            },
+            plane_size,
+            plane_offset: plane_size / 2.,
+            center_radius: 0.25,
+            ring_radius: 1.0,
+            ring_thickness: 0.04,
+            ring_angle: FRAC_PI_2,
+            meshes: GizmoHandleMeshes::default(),
+        }
+    }
+}
+
+impl GizmoShapes {
+    /// Thin arrows with small plane handles near the center, and full rotation circles, like the
+    /// tools of the Unity editor.
+    pub fn unity() -> Self {
+        Self {
+            axis_length: 1.0,
+            shaft_radius: 0.015,
+            axis_tip: AxisTip::Cone {
+                height: 0.2,
+                radius: 0.07,
+            },
+            plane_size: 0.25,
+            plane_offset: 0.2,
+            center_radius: 0.1,
+            ring_radius: 1.0,
+            ring_thickness: 0.015,
+            ring_angle: TAU,
+            meshes: GizmoHandleMeshes::default(),
+        }
+    }
+
+    /// Long arrows with small plane handles further out, a small center and full rotation
+    /// circles, like the gizmo of Blender.
+    pub fn blender() -> Self {
+        Self {
+            axis_length: 1.2,
+            shaft_radius: 0.02,
+            axis_tip: AxisTip::Cone {
+                height: 0.22,
+                radius: 0.06,
+            },
+            plane_size: 0.18,
+            plane_offset: 0.45,
+            center_radius: 0.12,
+            ring_radius: 1.1,
+            ring_thickness: 0.02,
+            ring_angle: TAU,
+            meshes: GizmoHandleMeshes::default(),
+        }
+    }
+
+    /// Bare axis lines without tips or plane handles, a small center, and thin rotation arcs.
+    pub fn minimal() -> Self {
+        Self {
+            axis_length: 1.2,
+            shaft_radius: 0.015,
+            axis_tip: AxisTip::None,
+            plane_size: 0.0,
+            plane_offset: 0.0,
+            center_radius: 0.08,
+            ring_radius: 1.0,
+            ring_thickness: 0.015,
+            ring_angle: FRAC_PI_2,
+            meshes: GizmoHandleMeshes::default(),
+        }
+    }
+
+    /// Uses `meshes` in place of the built-in meshes they provide.
+    pub fn with_meshes(mut self, meshes: GizmoHandleMeshes) -> Self {
+        self.meshes = meshes;
+        self
+    }
+}
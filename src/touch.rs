@@ -0,0 +1,232 @@
+use bevy::{
+    input::{mouse::MouseButtonInput, touch::TouchInput},
+    prelude::*,
+    render::view::RenderLayers,
+};
+use bevy_mod_picking::{
+    focus::HoverMap,
+    pointer::PointerId,
+    prelude::Pickable,
+    selection::{NoDeselect, PickSelection},
+};
+
+use crate::{
+    gizmo_material::GizmoMaterial,
+    handle_shadow::{spawn_handle_shadow, HandleShadow},
+    GizmoPickCamera, GizmoPickSource, GizmoTransformable, InitialTransform, TransformGizmo,
+    TransformGizmoInteraction, TransformGizmoSettings,
+};
+
+/// An invisible, enlarged copy of a gizmo handle that makes the handle easier to hit with a
+/// finger. Only pickable while touch input is in use.
+#[derive(Component)]
+pub struct TouchHitArea {
+    scale: Vec3,
+}
+
+impl HandleShadow for TouchHitArea {
+    fn offset(&self) -> Transform {
+        Transform::from_scale(self.scale)
+    }
+}
+
+/// The mesh of the touch hit area of a built-in handle, thicker than the handle but no longer.
+/// Handles without one, such as custom handles, are hit through a scaled copy of their own mesh.
+#[derive(Component)]
+pub struct TouchHitMesh(pub Handle<Mesh>);
+
+/// Whether the last pointer input came from a touchscreen rather than a mouse.
+#[derive(Resource, Default)]
+pub struct TouchInUse(bool);
+
+/// Spawns a touch hit area as a child of every new gizmo handle.
+pub fn spawn_touch_hit_areas(
+    settings: Res<TransformGizmoSettings>,
+    handles: Query<
+        (
+            Entity,
+            &Handle<Mesh>,
+            Option<&TouchHitMesh>,
+            Option<&RenderLayers>,
+        ),
+        Added<TransformGizmoInteraction>,
+    >,
+    mut hidden_material: Local<Option<Handle<GizmoMaterial>>>,
+    mut materials: ResMut<Assets<GizmoMaterial>>,
+    mut commands: Commands,
+) {
+    let material = hidden_material
+        .get_or_insert_with(|| materials.add(GizmoMaterial::from(Color::NONE)))
+        .clone();
+    for (entity, mesh, hit_mesh, render_layers) in &handles {
+        let (mesh, scale) = match hit_mesh {
+            Some(TouchHitMesh(hit_mesh)) => (hit_mesh, Vec3::ONE),
+            None => (mesh, Vec3::splat(settings.touch_hit_scale)),
+        };
+        spawn_handle_shadow(
+            &mut commands,
+            entity,
+            mesh,
+            render_layers,
+            material.clone(),
+            (TouchHitArea { scale }, NoDeselect),
+        );
+    }
+}
+
+/// Tracks whether touch input is in use, and makes the touch hit areas pickable accordingly. Runs
+/// before picking, so the first touch already hits the enlarged areas.
+pub fn update_touch_hit_areas(
+    mut touch_in_use: ResMut<TouchInUse>,
+    mut touch_events: EventReader<TouchInput>,
+    mut mouse_events: EventReader<MouseButtonInput>,
+    mut hit_areas: Query<(&Parent, &mut Pickable), With<TouchHitArea>>,
+    handles: Query<&Pickable, Without<TouchHitArea>>,
+) {
+    if touch_events.read().count() > 0 {
+        touch_in_use.0 = true;
+    }
+    if mouse_events.read().count() > 0 {
+        touch_in_use.0 = false;
+    }
+    for (parent, mut pickable) in &mut hit_areas {
+        // Handles that can't be picked, like fully faded ones, can't be touched either.
+        let handle_pickable = handles
+            .get(parent.get())
+            .map_or(true, |pickable| pickable.should_emit_events);
+        let new_pickable = if touch_in_use.0 && handle_pickable {
+            Pickable::default()
+        } else {
+            Pickable::IGNORE
+        };
+        if *pickable != new_pickable {
+            *pickable = new_pickable;
+        }
+    }
+}
+
+/// A two-finger gesture in progress.
+pub struct PinchGesture {
+    fingers: (u64, u64),
+    pick_camera: Entity,
+    pivot: Vec3,
+    start_distance: f32,
+    start_angle: f32,
+}
+
+/// Scales the selection uniformly by pinching two fingers on it, and rotates it around the view
+/// axis by twisting them.
+#[allow(clippy::too_many_arguments)]
+pub fn pinch_selection(
+    settings: Res<TransformGizmoSettings>,
+    touches: Res<Touches>,
+    hover_map: Res<HoverMap>,
+    gizmos: Query<(&TransformGizmo, &GizmoPickCamera, &GlobalTransform)>,
+    cameras: Query<&GlobalTransform, With<GizmoPickSource>>,
+    mut selected: Query<
+        (
+            Entity,
+            &PickSelection,
+            Option<&Parent>,
+            &mut Transform,
+            Option<&InitialTransform>,
+        ),
+        With<GizmoTransformable>,
+    >,
+    global_transforms: Query<&GlobalTransform>,
+    mut gesture: Local<Option<PinchGesture>>,
+    mut commands: Commands,
+) {
+    if let Some(current) = gesture.as_ref() {
+        let (first, second) = current.fingers;
+        let (Some(first), Some(second)) = (touches.get_pressed(first), touches.get_pressed(second))
+        else {
+            // A finger was lifted, end the gesture.
+            for (entity, .., initial) in &selected {
+                if initial.is_some() {
+                    commands.entity(entity).remove::<InitialTransform>();
+                }
+            }
+            *gesture = None;
+            return;
+        };
+        let Ok(camera_transform) = cameras.get(current.pick_camera) else {
+            return;
+        };
+        let fingers = second.position() - first.position();
+        let ratio = fingers.length() / current.start_distance;
+        // Screen space is y-down, so a counter-clockwise twist decreases the angle.
+        let twist = current.start_angle - fingers.y.atan2(fingers.x);
+        let rotation = if settings.allow_rotation {
+            Quat::from_axis_angle(camera_transform.back(), twist)
+        } else {
+            Quat::IDENTITY
+        };
+        for (_, selection, parent, mut local_transform, initial) in &mut selected {
+            let (true, Some(initial)) = (selection.is_selected, initial) else {
+                continue;
+            };
+            let initial = initial.transform;
+            let new_transform = Transform {
+                translation: current.pivot
+                    + rotation * (initial.translation - current.pivot) * ratio,
+                rotation: rotation * initial.rotation,
+                scale: initial.scale * ratio,
+            };
+            let parent_global_transform = parent
+                .and_then(|parent| global_transforms.get(parent.get()).ok())
+                .unwrap_or(&GlobalTransform::IDENTITY);
+            let local =
+                parent_global_transform.compute_matrix().inverse() * new_transform.compute_matrix();
+            local_transform.set_if_neq(Transform::from_matrix(local));
+        }
+        return;
+    }
+
+    // Start a gesture when two fingers are down, at least one of them on the selection, and no
+    // handle is being dragged.
+    let fingers: Vec<&bevy::input::touch::Touch> = touches.iter().collect();
+    let [first, second] = fingers[..] else {
+        return;
+    };
+    let Some(camera) = [first, second].iter().find_map(|touch| {
+        let hovered = hover_map.get(&PointerId::Touch(touch.id()))?;
+        hovered.iter().find_map(|(entity, hit)| {
+            let (_, selection, ..) = selected.get(*entity).ok()?;
+            selection.is_selected.then_some(hit.camera)
+        })
+    }) else {
+        return;
+    };
+    if gizmos
+        .iter()
+        .any(|(gizmo, ..)| gizmo.current_interaction().is_some())
+    {
+        return;
+    }
+    // Pivot around the gizmo of the camera the selection was touched through.
+    let Some((_, pick_camera, gizmo_transform)) = gizmos
+        .iter()
+        .find(|(_, pick_camera, _)| pick_camera.0 == camera)
+    else {
+        return;
+    };
+    let start = second.position() - first.position();
+    if start.length() <= f32::EPSILON {
+        return;
+    }
+    for (entity, selection, ..) in &selected {
+        if let (true, Ok(transform)) = (selection.is_selected, global_transforms.get(entity)) {
+            commands.entity(entity).insert(InitialTransform {
+                transform: transform.compute_transform(),
+            });
+        }
+    }
+    *gesture = Some(PinchGesture {
+        fingers: (first.id(), second.id()),
+        pick_camera: pick_camera.0,
+        pivot: gizmo_transform.translation(),
+        start_distance: start.length(),
+        start_angle: start.y.atan2(start.x),
+    });
+}
@@ -0,0 +1,104 @@
+use bevy::{prelude::*, render::view::RenderLayers, utils::HashMap};
+
+use crate::{
+    gizmo_material::GizmoMaterial,
+    handle_shadow::{spawn_handle_shadow, HandleShadow},
+    GizmoDepthMode, TransformGizmoInteraction, TransformGizmoSettings,
+};
+
+/// A copy of a gizmo handle, drawn dimmed where the handle is hidden behind the scene when the
+/// gizmo is in [`GizmoDepthMode::XRay`].
+#[derive(Component)]
+pub struct OccludedGhost;
+
+impl HandleShadow for OccludedGhost {}
+
+/// Spawns an occluded ghost as a child of every new gizmo handle. Handles sharing a material share
+/// the material of their ghosts as well.
+pub fn spawn_occluded_ghosts(
+    settings: Res<TransformGizmoSettings>,
+    handles: Query<
+        (
+            Entity,
+            &Handle<Mesh>,
+            &Handle<GizmoMaterial>,
+            Option<&RenderLayers>,
+        ),
+        Added<TransformGizmoInteraction>,
+    >,
+    mut ghost_materials: Local<HashMap<AssetId<GizmoMaterial>, Handle<GizmoMaterial>>>,
+    mut materials: ResMut<Assets<GizmoMaterial>>,
+    mut commands: Commands,
+) {
+    for (entity, mesh, material, render_layers) in &handles {
+        let ghost_material = ghost_materials
+            .entry(material.id())
+            .or_insert_with(|| {
+                let color = materials.get(material).map_or(Color::NONE, |m| m.color);
+                materials.add(GizmoMaterial {
+                    color,
+                    occluded: true,
+                })
+            })
+            .clone();
+        let ghost = spawn_handle_shadow(
+            &mut commands,
+            entity,
+            mesh,
+            render_layers,
+            ghost_material,
+            OccludedGhost,
+        );
+        commands.entity(ghost).insert(ghost_visibility(&settings));
+    }
+}
+
+fn ghost_visibility(settings: &TransformGizmoSettings) -> Visibility {
+    match settings.depth_mode {
+        GizmoDepthMode::AlwaysOnTop => Visibility::Hidden,
+        GizmoDepthMode::XRay { .. } => Visibility::Inherited,
+    }
+}
+
+/// Shows the occluded ghosts only in x-ray mode.
+pub fn update_ghost_visibility(
+    settings: Res<TransformGizmoSettings>,
+    mut ghosts: Query<&mut Visibility, With<OccludedGhost>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for mut visibility in &mut ghosts {
+        visibility.set_if_neq(ghost_visibility(&settings));
+    }
+}
+
+/// Colors the ghosts like their handles, dimmed by the occluded alpha.
+pub fn update_ghost_colors(
+    settings: Res<TransformGizmoSettings>,
+    ghosts: Query<(&Parent, &Handle<GizmoMaterial>), With<OccludedGhost>>,
+    handles: Query<&Handle<GizmoMaterial>, Without<OccludedGhost>>,
+    mut materials: ResMut<Assets<GizmoMaterial>>,
+) {
+    let GizmoDepthMode::XRay { occluded_alpha } = settings.depth_mode else {
+        return;
+    };
+    for (parent, ghost_material) in &ghosts {
+        let Some(color) = handles
+            .get(parent.get())
+            .ok()
+            .and_then(|handle| materials.get(handle))
+            .map(|material| material.color)
+        else {
+            continue;
+        };
+        let color = color.with_a(color.a() * occluded_alpha);
+        // Only write to changed materials, to avoid re-uploading them every frame.
+        if materials
+            .get(ghost_material)
+            .is_some_and(|m| m.color != color)
+        {
+            materials.get_mut(ghost_material).unwrap().color = color;
+        }
+    }
+}